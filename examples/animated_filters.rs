@@ -49,75 +49,49 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
     commands.spawn((
         PxFilter(fade_to_black.clone()),
         PxFilterLayers::single_clip(Layer(1)),
-        PxAnimation {
-            on_finish: PxAnimationFinishBehavior::Mark,
-            ..default()
-        },
+        PxAnimation::once(),
     ));
 
     // Loop
     commands.spawn((
         PxFilter(fade_to_black.clone()),
         PxFilterLayers::single_clip(Layer(2)),
-        PxAnimation {
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        PxAnimation::looping(),
     ));
 
     // Backward
     commands.spawn((
         PxFilter(fade_to_black.clone()),
         PxFilterLayers::single_clip(Layer(3)),
-        PxAnimation {
-            direction: PxAnimationDirection::Backward,
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        PxAnimation::looping().backward(),
     ));
 
     // Faster
     commands.spawn((
         PxFilter(fade_to_black.clone()),
         PxFilterLayers::single_clip(Layer(5)),
-        PxAnimation {
-            duration: PxAnimationDuration::millis_per_animation(500),
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        PxAnimation::looping().with_duration(PxAnimationDuration::millis_per_animation(500)),
     ));
 
     // Slower
     commands.spawn((
         PxFilter(fade_to_black.clone()),
         PxFilterLayers::single_clip(Layer(4)),
-        PxAnimation {
-            duration: PxAnimationDuration::millis_per_animation(2000),
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        PxAnimation::looping().with_duration(PxAnimationDuration::millis_per_animation(2000)),
     ));
 
     // Duration per frame
     commands.spawn((
         PxFilter(fade_to_black.clone()),
         PxFilterLayers::single_clip(Layer(6)),
-        PxAnimation {
-            duration: PxAnimationDuration::millis_per_frame(1000),
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        PxAnimation::looping().with_duration(PxAnimationDuration::millis_per_frame(1000)),
     ));
 
     // Dither between frames
     commands.spawn((
         PxFilter(fade_to_black),
         PxFilterLayers::single_clip(Layer(7)),
-        PxAnimation {
-            on_finish: PxAnimationFinishBehavior::Loop,
-            frame_transition: PxAnimationFrameTransition::Dither,
-            ..default()
-        },
+        PxAnimation::looping().dithered(),
     ));
 }
 
@@ -38,10 +38,7 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
         PxSprite(runner.clone()),
         PxPosition(IVec2::new(13, 0)),
         PxAnchor::BottomLeft,
-        PxAnimation {
-            on_finish: PxAnimationFinishBehavior::Mark,
-            ..default()
-        },
+        PxAnimation::once(),
     ));
 
     // Loop
@@ -49,10 +46,7 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
         PxSprite(runner.clone()),
         PxPosition(IVec2::new(26, 0)),
         PxAnchor::BottomLeft,
-        PxAnimation {
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        PxAnimation::looping(),
     ));
 
     // Backward
@@ -60,11 +54,7 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
         PxSprite(runner.clone()),
         PxPosition(IVec2::new(39, 0)),
         PxAnchor::BottomLeft,
-        PxAnimation {
-            direction: PxAnimationDirection::Backward,
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        PxAnimation::looping().backward(),
     ));
 
     // Faster
@@ -72,11 +62,7 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
         PxSprite(runner.clone()),
         PxPosition(IVec2::new(13, 18)),
         PxAnchor::BottomLeft,
-        PxAnimation {
-            duration: PxAnimationDuration::millis_per_animation(500),
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        PxAnimation::looping().with_duration(PxAnimationDuration::millis_per_animation(500)),
     ));
 
     // Slower
@@ -84,11 +70,7 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
         PxSprite(runner.clone()),
         PxPosition(IVec2::new(0, 18)),
         PxAnchor::BottomLeft,
-        PxAnimation {
-            duration: PxAnimationDuration::millis_per_animation(2000),
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        PxAnimation::looping().with_duration(PxAnimationDuration::millis_per_animation(2000)),
     ));
 
     // Duration per frame
@@ -96,11 +78,7 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
         PxSprite(runner.clone()),
         PxPosition(IVec2::new(26, 18)),
         PxAnchor::BottomLeft,
-        PxAnimation {
-            duration: PxAnimationDuration::millis_per_frame(1000),
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        PxAnimation::looping().with_duration(PxAnimationDuration::millis_per_frame(1000)),
     ));
 
     // Dither between frames
@@ -108,11 +86,7 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
         PxSprite(runner),
         PxPosition(IVec2::new(39, 18)),
         PxAnchor::BottomLeft,
-        PxAnimation {
-            on_finish: PxAnimationFinishBehavior::Loop,
-            frame_transition: PxAnimationFrameTransition::Dither,
-            ..default()
-        },
+        PxAnimation::looping().dithered(),
     ));
 }
 
@@ -30,31 +30,26 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
         PxText {
             value: "LOOPED ANIMATION ⭐🙂⭐".to_string(),
             typeface: typeface.clone(),
+            ..default()
         },
         PxRect(IRect::new(0, 0, 64, 64)),
         PxAnchor::TopCenter,
-        PxAnimation {
-            // Use millis_per_animation to have each character loop at the same time
-            duration: PxAnimationDuration::millis_per_frame(333),
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        // Use millis_per_animation to have each character loop at the same time
+        PxAnimation::looping().with_duration(PxAnimationDuration::millis_per_frame(333)),
     ));
 
     commands.spawn((
         PxText {
             value: "DITHERED ANIMATION 🙂⭐🙂".to_string(),
             typeface,
+            ..default()
         },
         PxRect(IRect::new(0, 0, 64, 64)),
         PxAnchor::BottomCenter,
-        PxAnimation {
-            // Use millis_per_animation to have each character loop at the same time
-            duration: PxAnimationDuration::millis_per_frame(333),
-            on_finish: PxAnimationFinishBehavior::Loop,
-            frame_transition: PxAnimationFrameTransition::Dither,
-            ..default()
-        },
+        // Use millis_per_animation to have each character loop at the same time
+        PxAnimation::looping()
+            .with_duration(PxAnimationDuration::millis_per_frame(333))
+            .dithered(),
     ));
 }
 
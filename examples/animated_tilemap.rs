@@ -44,24 +44,17 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
             tiles: tiles.clone(),
             tileset: tileset.clone(),
         },
-        PxAnimation {
-            // Use millis_per_animation to have each tile loop at the same time
-            duration: PxAnimationDuration::millis_per_frame(250),
-            on_finish: PxAnimationFinishBehavior::Loop,
-            ..default()
-        },
+        // Use millis_per_animation to have each tile loop at the same time
+        PxAnimation::looping().with_duration(PxAnimationDuration::millis_per_frame(250)),
     ));
 
     commands.spawn((
         PxMap { tiles, tileset },
         PxPosition(IVec2::new(8, 0)),
-        PxAnimation {
-            // Use millis_per_animation to have each tile loop at the same time
-            duration: PxAnimationDuration::millis_per_frame(250),
-            on_finish: PxAnimationFinishBehavior::Loop,
-            frame_transition: PxAnimationFrameTransition::Dither,
-            ..default()
-        },
+        // Use millis_per_animation to have each tile loop at the same time
+        PxAnimation::looping()
+            .with_duration(PxAnimationDuration::millis_per_frame(250))
+            .dithered(),
     ));
 }
 
@@ -0,0 +1,49 @@
+// In this program, a tilemap is spawned whose tiles have different animation lengths
+
+use bevy::prelude::*;
+use seldom_pixel::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    resolution: Vec2::splat(512.).into(),
+                    ..default()
+                }),
+                ..default()
+            }),
+            PxPlugin::<Layer>::new(UVec2::splat(16), "palette/palette_1.palette.png"),
+        ))
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_systems(Startup, init)
+        .run();
+}
+
+fn init(assets: Res<AssetServer>, mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    // The tiles in this tileset are 3x2, and have 5, 3, 1, and 2 frames
+    let mut tiles = PxTiles::new(UVec2::new(5, 8));
+
+    for x in 0..5 {
+        for y in 0..8 {
+            tiles.set(
+                Some(commands.spawn(PxTile::from((x + y) % 4)).id()),
+                UVec2::new(x, y),
+            );
+        }
+    }
+
+    // Spawn the map. Each tile loops over its own frames.
+    commands.spawn((
+        PxMap {
+            tiles,
+            tileset: assets.load("tileset/mixed.px_tileset.png"),
+        },
+        PxAnimation::looping().with_duration(PxAnimationDuration::millis_per_frame(250)),
+    ));
+}
+
+#[px_layer]
+struct Layer;
@@ -53,7 +53,7 @@ fn move_camera(
     mut camera: ResMut<PxCamera>,
 ) {
     let mut camera_pos = camera_poses.single_mut();
-    **camera_pos += IVec2::new(
+    *camera_pos += IVec2::new(
         keys.pressed(KeyCode::ArrowRight) as i32 - keys.pressed(KeyCode::ArrowLeft) as i32,
         keys.pressed(KeyCode::ArrowUp) as i32 - keys.pressed(KeyCode::ArrowDown) as i32,
     )
@@ -74,7 +74,7 @@ fn move_mage(mut mages: Query<&mut PxPosition, With<Mage>>) {
     if let Some(delta) =
         [IVec2::X, -IVec2::X, IVec2::Y, -IVec2::Y].get(thread_rng().gen_range(0..50))
     {
-        **mages.single_mut() += *delta;
+        *mages.single_mut() += *delta;
     }
 }
 
@@ -0,0 +1,56 @@
+// In this program, curved lines are spawned
+
+use bevy::prelude::*;
+use seldom_pixel::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    resolution: Vec2::splat(512.).into(),
+                    ..default()
+                }),
+                ..default()
+            }),
+            PxPlugin::<Layer>::new(UVec2::splat(32), "palette/palette_1.palette.png"),
+        ))
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_systems(Startup, init)
+        .run();
+}
+
+fn init(assets: Res<AssetServer>, mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    let mage = assets.load("sprite/mage.px_sprite.png");
+    let invert = assets.load("filter/invert.px_filter.png");
+
+    commands.spawn((PxSprite(mage), PxPosition(IVec2::splat(16))));
+
+    // Spawn a Bézier curve. The curve starts and ends at the first and last points,
+    // and is pulled toward the points in between.
+    commands.spawn((
+        PxLine::from_bezier(&[(2, 4).into(), (16, 30).into(), (29, 4).into()], 16),
+        PxFilterLayers::single_over(Layer),
+        PxFilter(invert.clone()),
+    ));
+
+    // Spawn a Catmull-Rom spline, which passes through every point
+    commands.spawn((
+        PxLine::from_catmull_rom(
+            &[
+                (2, 28).into(),
+                (10, 20).into(),
+                (20, 28).into(),
+                (29, 18).into(),
+            ],
+            8,
+        ),
+        PxFilterLayers::single_over(Layer),
+        PxFilter(invert),
+    ));
+}
+
+#[px_layer]
+struct Layer;
@@ -0,0 +1,65 @@
+// In this program, a custom drawable draws a starfield
+
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+use seldom_pixel::{image::PxImage, prelude::*};
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    resolution: Vec2::splat(512.).into(),
+                    ..default()
+                }),
+                ..default()
+            }),
+            PxPlugin::<Layer>::new(UVec2::splat(64), "palette/palette_1.palette.png"),
+        ))
+        // Drawables must be registered with the layer type they're drawn on
+        .add_px_drawable::<Starfield, Layer>()
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_systems(Startup, init)
+        .run();
+}
+
+fn init(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    let mut rng = thread_rng();
+    commands.spawn((
+        Starfield {
+            stars: (0..48)
+                .map(|_| IVec2::new(rng.gen_range(0..64), rng.gen_range(0..64)))
+                .collect(),
+            // Palette index of the stars
+            color: 1,
+        },
+        PxPosition::default(),
+        Layer,
+    ));
+}
+
+#[derive(Component, Clone)]
+struct Starfield {
+    // Star positions relative to the entity, with y up
+    stars: Vec<IVec2>,
+    color: u8,
+}
+
+impl PxDrawable for Starfield {
+    fn draw(&self, image: &mut PxImage<Option<u8>>, position: IVec2) {
+        for &star in &self.stars {
+            let star = position + star;
+
+            // `PxImage` is indexed with y down
+            image.set_pixel(
+                IVec2::new(star.x, image.height() as i32 - 1 - star.y),
+                Some(self.color),
+            );
+        }
+    }
+}
+
+#[px_layer]
+struct Layer;
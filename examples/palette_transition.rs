@@ -0,0 +1,95 @@
+// In this program, the palette crossfades to another palette when you press tab
+
+use bevy::prelude::*;
+use seldom_pixel::{
+    palette::{Palette, PaletteHandle, PxPaletteTransition},
+    prelude::*,
+};
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    resolution: Vec2::splat(512.).into(),
+                    ..default()
+                }),
+                ..default()
+            }),
+            PxPlugin::<Layer>::new(UVec2::splat(32), "palette/palette_1.palette.png"),
+        ))
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_systems(Startup, init)
+        .add_systems(Update, (start_transition, advance_transition))
+        .run();
+}
+
+#[derive(Resource)]
+struct GameAssets {
+    palette_1: Handle<Palette>,
+    palette_2: Handle<Palette>,
+}
+
+fn init(assets: Res<AssetServer>, mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    commands.spawn((
+        PxSprite(assets.load("sprite/mage.px_sprite.png")),
+        PxPosition(IVec2::splat(16)),
+    ));
+
+    commands.insert_resource(GameAssets {
+        palette_1: assets.load("palette/palette_1.palette.png"),
+        palette_2: assets.load("palette/palette_2.palette.png"),
+    });
+}
+
+fn start_transition(
+    transition: Option<Res<PxPaletteTransition>>,
+    palette: Res<PaletteHandle>,
+    assets: Res<GameAssets>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(KeyCode::Tab) || transition.is_some() {
+        return;
+    }
+
+    // Transition to whichever palette isn't currently displayed
+    let to = if **palette == assets.palette_1 {
+        &assets.palette_2
+    } else {
+        &assets.palette_1
+    };
+
+    commands.insert_resource(PxPaletteTransition {
+        from: (**palette).clone(),
+        to: to.clone(),
+        progress: 0.,
+    });
+}
+
+// Seconds that a transition takes
+const DURATION: f32 = 1.;
+
+fn advance_transition(
+    transition: Option<ResMut<PxPaletteTransition>>,
+    mut palette: ResMut<PaletteHandle>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let Some(mut transition) = transition else {
+        return;
+    };
+
+    transition.progress += time.delta_secs() / DURATION;
+
+    if transition.progress >= 1. {
+        // The transition is done, so the new palette becomes the game's palette
+        **palette = transition.to.clone();
+        commands.remove_resource::<PxPaletteTransition>();
+    }
+}
+
+#[px_layer]
+struct Layer;
@@ -33,32 +33,51 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
                     assets.load("sprite/snow_1.px_sprite.png"),
                     assets.load("sprite/snow_2.px_sprite.png"),
                 ],
+                // Each particle gets one of these filters, for some color variation
+                filters: vec![
+                    assets.load("filter/identity.px_filter.png"),
+                    assets.load("filter/dim.px_filter.png"),
+                ],
                 // Range where the particles can spawn
-                range: IRect::new(-4, 36, 36, 36),
+                range: PxEmitterShape::Rect(IRect::new(-4, 36, 36, 36)),
+                // If true, the range would be relative to the emitter's position
+                local: false,
                 // Range of how often the particles spawn
                 frequency: PxEmitterFrequency::new(
                     Duration::from_millis(800),
                     Duration::from_millis(1500),
                 ),
                 // `PxEmitterSimulation::Simulate` spawns particles
-                // as soon as the `PxEmitterBundle` is spawned, with values as if they had been spawned
+                // as soon as the `PxEmitter` is spawned, with values as if they had been spawned
                 // earlier. This is useful when an emitter comes into view,
                 // and you want it to look like it had been emitting particles all along.
                 simulation: PxEmitterSimulation::Simulate,
+                // `PxEmitterMode::Burst` would spawn a fixed number of particles at once instead
+                mode: PxEmitterMode::Continuous,
+                // Particle starting velocity. `PxEmitterVelocity::Cone` would launch particles
+                // in a random direction within a cone instead.
+                velocity: Some(PxEmitterVelocity::Fixed(Vec2::new(0., -2.5))),
+                // Each particle's velocity and lifetime vary a bit
+                velocity_jitter: Vec2::new(0.5, 0.5),
+                lifetime_jitter: Duration::from_secs(5),
+                // Caps how many of this emitter's particles can be alive at once
+                max_particles: Some(64),
+                fade: Some(PxParticleFade { start: 0.5 }),
+                // Set a lifetime to despawn the emitter after a while, optionally waiting
+                // for its particles to expire
+                lifetime: None,
+                despawn_after_particles: false,
+                // Start each particle's animation at a random frame
+                random_anim_phase: true,
                 // This function is run on each particle that spawns. It is run
                 // after all of the other components are added, so you can use this to override components.
                 on_spawn: Box::new(|particle: &mut EntityCommands| {
                     // Let's make each particle animated
-                    particle.insert(PxAnimation {
-                        on_finish: PxAnimationFinishBehavior::Loop,
-                        ..default()
-                    });
+                    particle.insert(PxAnimation::looping());
                 }),
             },
             // Particle lifetime
             PxParticleLifetime(Duration::from_secs(30)),
-            // Particle starting velocity
-            PxVelocity(Vec2::new(0., -2.5)),
         ))
         .log_components();
 }
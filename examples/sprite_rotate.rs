@@ -0,0 +1,40 @@
+// In this program, sprites are spawned with each rotation
+
+use bevy::prelude::*;
+use seldom_pixel::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    resolution: Vec2::splat(512.).into(),
+                    ..default()
+                }),
+                ..default()
+            }),
+            PxPlugin::<Layer>::new(UVec2::splat(32), "palette/palette_1.palette.png"),
+        ))
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_systems(Startup, init)
+        .run();
+}
+
+fn init(assets: Res<AssetServer>, mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    let mage = assets.load("sprite/mage.px_sprite.png");
+
+    // Spawn a sprite with each rotation
+    for (rotation, position) in [
+        (PxRotation::None, IVec2::new(8, 24)),
+        (PxRotation::Quarter, IVec2::new(24, 24)),
+        (PxRotation::Half, IVec2::new(24, 8)),
+        (PxRotation::ThreeQuarter, IVec2::new(8, 8)),
+    ] {
+        commands.spawn((PxSprite(mage.clone()), PxPosition(position), rotation));
+    }
+}
+
+#[px_layer]
+struct Layer;
@@ -47,25 +47,20 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
             input_map: InputMap::default().with(Action::Cast, KeyCode::Space),
             ..default()
         },
+        // Show the idle sprite while idle
+        PxAnimationOnState::<Idle>::new(idle, None),
+        // Play the cast animation while casting. It finishes by adding `Done`.
+        PxAnimationOnState::<Cast>::new(
+            cast,
+            Some(PxAnimation {
+                duration: PxAnimationDuration::millis_per_animation(2000),
+                on_finish: PxAnimationFinishBehavior::Done,
+                ..default()
+            }),
+        ),
         StateMachine::default()
             .trans::<Idle, _>(just_pressed(Action::Cast), Cast)
-            .on_enter::<Cast>(move |entity| {
-                entity.insert((
-                    PxSprite(cast.clone()),
-                    PxAnimation {
-                        duration: PxAnimationDuration::millis_per_animation(2000),
-                        on_finish: PxAnimationFinishBehavior::Done,
-                        ..default()
-                    },
-                ));
-            })
-            .on_exit::<Cast>(|entity| {
-                entity.remove::<PxAnimation>();
-            })
             .trans::<Cast, _>(done(None), Idle)
-            .on_enter::<Idle>(move |entity| {
-                entity.insert(PxSprite(idle.clone()));
-            })
             .set_trans_logging(true),
         Idle,
     ));
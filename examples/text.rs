@@ -1,5 +1,8 @@
 // In this program, text is spawned
 
+// This example doubles as a smoke test for the web. Build it for `wasm32-unknown-unknown`
+// (for example, with `trunk`), and it should start without panicking while the palette loads.
+
 use bevy::prelude::*;
 use seldom_pixel::prelude::*;
 
@@ -28,6 +31,7 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
         PxText {
             value: "THE MITOCHONDRIA IS THE POWERHOUSE OF THE CELL".to_string(),
             typeface: assets.load("typeface/typeface.px_typeface.png"),
+            ..default()
         },
         PxRect(IRect::new(0, 0, 64, 64)),
     ));
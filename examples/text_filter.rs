@@ -28,6 +28,7 @@ fn init(assets: Res<AssetServer>, mut commands: Commands) {
         PxText {
             value: "THE MITOCHONDRIA IS THE POWERHOUSE OF THE CELL".to_string(),
             typeface: assets.load("typeface/typeface.px_typeface.png"),
+            ..default()
         },
         PxRect(IRect::new(0, 0, 64, 64)),
         PxFilter(assets.load("filter/dim.px_filter.png")),
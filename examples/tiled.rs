@@ -0,0 +1,57 @@
+// In this program, a tilemap is loaded from a Tiled map
+
+use bevy::prelude::*;
+use seldom_pixel::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    resolution: Vec2::splat(512.).into(),
+                    ..default()
+                }),
+                ..default()
+            }),
+            PxPlugin::<Layer>::new(UVec2::splat(16), "palette/palette_1.palette.png"),
+        ))
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_systems(Startup, init)
+        .add_systems(Update, spawn_map)
+        .run();
+}
+
+#[derive(Resource)]
+struct MapHandle(Handle<PxTiledMap>);
+
+fn init(assets: Res<AssetServer>, mut commands: Commands) {
+    commands.spawn(Camera2d);
+    commands.insert_resource(MapHandle(assets.load("map/map.tmx")));
+}
+
+// Spawn the map once the Tiled map has loaded
+fn spawn_map(
+    map: Option<Res<MapHandle>>,
+    tiled_maps: Res<Assets<PxTiledMap>>,
+    assets: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let Some(map) = map else {
+        return;
+    };
+
+    let Some(tiled_map) = tiled_maps.get(&map.0) else {
+        return;
+    };
+
+    let tiles = tiled_map.spawn_tiles(&mut commands);
+
+    commands.spawn(PxMap {
+        tiles,
+        tileset: assets.load("tileset/tileset.px_tileset.png"),
+    });
+    commands.remove_resource::<MapHandle>();
+}
+
+#[px_layer]
+struct Layer;
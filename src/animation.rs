@@ -1,7 +1,11 @@
 //! Animation
 
+#[cfg(feature = "state")]
+use std::marker::PhantomData;
 use std::time::Duration;
 
+#[cfg(feature = "state")]
+use bevy::ecs::{component::ComponentId, world::DeferredWorld};
 use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
 use bevy::utils::Instant;
 
@@ -15,6 +19,11 @@ use crate::{
 
 pub(crate) fn plug(app: &mut App) {
     app.add_plugins(ExtractResourcePlugin::<LastUpdate>::default())
+        .init_resource::<PxTimePaused>()
+        .add_systems(
+            PreUpdate,
+            delay_paused_animations.run_if(resource_equals(PxTimePaused(true))),
+        )
         .add_systems(
             PostUpdate,
             (
@@ -118,6 +127,145 @@ impl Default for PxAnimation {
     }
 }
 
+impl PxAnimation {
+    /// Creates an animation that loops
+    pub fn looping() -> Self {
+        Self {
+            on_finish: PxAnimationFinishBehavior::Loop,
+            ..default()
+        }
+    }
+
+    /// Creates an animation that plays once and adds [`PxAnimationFinished`] when it finishes
+    pub fn once() -> Self {
+        Self {
+            on_finish: PxAnimationFinishBehavior::Mark,
+            ..default()
+        }
+    }
+
+    /// Plays the animation backward
+    pub fn backward(mut self) -> Self {
+        self.direction = PxAnimationDirection::Backward;
+        self
+    }
+
+    /// Dithers between frames, smoothing the animation
+    pub fn dithered(mut self) -> Self {
+        self.frame_transition = PxAnimationFrameTransition::Dither;
+        self
+    }
+
+    /// Sets the animation's [`PxAnimationDuration`]
+    pub fn with_duration(mut self, duration: PxAnimationDuration) -> Self {
+        self.duration = duration;
+        self
+    }
+}
+
+/// Resource that freezes animations, particles, and movement while it's `true`. Paused entities
+/// are still drawn as they were when the pause started. Pausing [`Time<Virtual>`] also stops
+/// movement, since it uses [`Time`], but animations and particles run on [`Time<Real>`],
+/// so they only freeze with this resource.
+#[derive(Debug, Default, Deref, DerefMut, PartialEq, Resource)]
+pub struct PxTimePaused(pub bool);
+
+// Pushes animations' start times forward by the time spent paused, so they resume where they were
+fn delay_paused_animations(mut animations: Query<&mut PxAnimation>, time: Res<Time<Real>>) {
+    for mut animation in &mut animations {
+        animation.start += time.delta();
+    }
+}
+
+/// Shows a sprite, and optionally plays an animation, while the entity is in the state `S`.
+/// Add one for each state of a `seldom_state` `StateMachine`. When `S` is added,
+/// the entity's [`PxSprite`] is replaced and the animation is restarted. When `S` is removed,
+/// the animation is removed. Requires the `state` feature.
+#[cfg(feature = "state")]
+#[derive(Component)]
+#[component(on_add = observe_state::<S>)]
+pub struct PxAnimationOnState<S: Component> {
+    /// The sprite to show
+    pub sprite: Handle<PxSpriteAsset>,
+    /// The animation to play, if any. Its start time is reset when the state is entered.
+    pub animation: Option<PxAnimation>,
+    _s: PhantomData<S>,
+}
+
+#[cfg(feature = "state")]
+impl<S: Component> PxAnimationOnState<S> {
+    /// Create a [`PxAnimationOnState`]
+    pub fn new(sprite: Handle<PxSpriteAsset>, animation: Option<PxAnimation>) -> Self {
+        Self {
+            sprite,
+            animation,
+            _s: PhantomData,
+        }
+    }
+
+    fn enter(&self, entity: &mut EntityCommands) {
+        entity.insert(PxSprite(self.sprite.clone()));
+
+        if let Some(animation) = self.animation {
+            entity.insert(PxAnimation {
+                start: Instant::now(),
+                ..animation
+            });
+        }
+    }
+}
+
+#[cfg(feature = "state")]
+impl<S: Component> Clone for PxAnimationOnState<S> {
+    fn clone(&self) -> Self {
+        Self::new(self.sprite.clone(), self.animation)
+    }
+}
+
+#[cfg(feature = "state")]
+fn observe_state<S: Component>(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    // The state may have been added alongside this component, before the observers existed
+    let entered = world
+        .entity(entity)
+        .contains::<S>()
+        .then(|| world.get::<PxAnimationOnState<S>>(entity).unwrap().clone());
+    let mut commands = world.commands();
+    let mut entity_commands = commands.entity(entity);
+
+    if let Some(state) = entered {
+        state.enter(&mut entity_commands);
+    }
+
+    entity_commands
+        .observe(enter_state::<S>)
+        .observe(exit_state::<S>);
+}
+
+#[cfg(feature = "state")]
+fn enter_state<S: Component>(
+    trigger: Trigger<OnAdd, S>,
+    states: Query<&PxAnimationOnState<S>>,
+    mut commands: Commands,
+) {
+    if let Ok(state) = states.get(trigger.entity()) {
+        state.enter(&mut commands.entity(trigger.entity()));
+    }
+}
+
+#[cfg(feature = "state")]
+fn exit_state<S: Component>(
+    trigger: Trigger<OnRemove, S>,
+    states: Query<&PxAnimationOnState<S>>,
+    mut commands: Commands,
+) {
+    if states
+        .get(trigger.entity())
+        .is_ok_and(|state| state.animation.is_some())
+    {
+        commands.entity(trigger.entity()).remove::<PxAnimation>();
+    }
+}
+
 /// Marks an animation that has finished. Automatically added to animations
 /// with [`PxAnimationFinishBehavior::Mark`]
 #[derive(Component, Debug)]
@@ -131,8 +279,8 @@ pub(crate) trait Animation {
         &self,
         param: Self::Param,
         image: &mut PxImageSliceMut<impl Pixel>,
-        frame: impl Fn(UVec2) -> usize,
-        filter: impl Fn(u8) -> u8,
+        frame: impl Fn(UVec2) -> usize + Sync,
+        filter: impl Fn(u8) -> u8 + Sync,
     );
 }
 
@@ -169,7 +317,7 @@ pub(crate) fn animate(
     frame_transition: PxAnimationFrameTransition,
     age: Duration,
     frame_count: usize,
-) -> impl Fn(UVec2) -> usize {
+) -> impl Fn(UVec2) -> usize + Sync {
     let (animation_duration, frame_duration) = match duration {
         PxAnimationDuration::PerAnimation(duration) => (duration, duration / frame_count as u32),
         PxAnimationDuration::PerFrame(duration) => (duration * frame_count as u32, duration),
@@ -209,10 +357,11 @@ pub(crate) fn animate(
         _ => 0,
     };
 
-    move |pos| {
-        (frame + (0b1000_0000_0000_0000 >> (pos.x % 4 + pos.y % 4 * 4) & dithering != 0) as usize)
-            % frame_count
-    }
+    move |pos| (frame + dither(dithering, pos) as usize) % frame_count
+}
+
+fn dither(dithering: u16, pos: UVec2) -> bool {
+    0b1000_0000_0000_0000 >> (pos.x % 4 + pos.y % 4 * 4) & dithering != 0
 }
 
 pub(crate) fn draw_animation<'a, A: Animation>(
@@ -228,7 +377,7 @@ pub(crate) fn draw_animation<'a, A: Animation>(
     )>,
     filters: impl IntoIterator<Item = &'a PxFilterAsset>,
 ) {
-    let mut filter: Box<dyn Fn(u8) -> u8> = Box::new(|pixel| pixel);
+    let mut filter: Box<dyn Fn(u8) -> u8 + Sync> = Box::new(|pixel| pixel);
     for filter_part in filters {
         let filter_part = filter_part.as_fn();
         filter = Box::new(move |pixel| filter_part(filter(pixel)));
@@ -255,6 +404,27 @@ pub(crate) fn draw_animation<'a, A: Animation>(
     }
 }
 
+// Rectangle of `image` that `draw_spatial` draws `spatial` in, with y down
+pub(crate) fn spatial_rect(
+    spatial: &impl Spatial,
+    image: &PxImage<impl Pixel>,
+    position: PxPosition,
+    anchor: PxAnchor,
+    canvas: PxCanvas,
+    camera: PxCamera,
+) -> IRect {
+    let size = spatial.frame_size().as_ivec2();
+    let position = *position - spatial.anchor_pos(anchor);
+    let position = camera.world_to_screen(position, canvas);
+    let position = IVec2::new(position.x, image.size().y as i32 - position.y);
+
+    IRect {
+        min: position - IVec2::new(0, size.y),
+        max: position + IVec2::new(size.x, 0),
+    }
+}
+
+// `opacity` comes with a scratch image, which translucent frames are drawn into before dithering
 pub(crate) fn draw_spatial<'a, A: Animation + Spatial>(
     spatial: &A,
     param: <A as Animation>::Param,
@@ -270,23 +440,41 @@ pub(crate) fn draw_spatial<'a, A: Animation + Spatial>(
         Duration,
     )>,
     filters: impl IntoIterator<Item = &'a PxFilterAsset>,
+    opacity: Option<(&PxOpacity, &mut PxImage<Option<u8>>)>,
     camera: PxCamera,
 ) {
-    let size = spatial.frame_size();
-    let position = *position - anchor.pos(size).as_ivec2();
-    let position = match canvas {
-        PxCanvas::World => position - *camera,
-        PxCanvas::Camera => position,
-    };
-    let position = IVec2::new(position.x, image.size().y as i32 - position.y);
-    let size = size.as_ivec2();
+    let size = spatial.frame_size().as_ivec2();
+    let rect = spatial_rect(spatial, image, position, anchor, canvas, camera);
+    let min = rect.min;
+    let mut image = image.slice_mut(rect);
+
+    match opacity {
+        Some((&PxOpacity(opacity), faded)) if opacity < 1. => {
+            faded.reset(size.as_uvec2());
+            draw_animation(
+                spatial,
+                param,
+                &mut faded.slice_all_mut(),
+                animation,
+                filters,
+            );
 
-    let mut image = image.slice_mut(IRect {
-        min: position - IVec2::new(0, size.y),
-        max: position + IVec2::new(size.x, 0),
-    });
+            // Dither in screen space so the pattern doesn't move with the entity
+            let dithering = DITHERING[(opacity.max(0.) * 16.) as usize];
+            let width = size.x;
+            faded.slice_all_mut().for_each_mut(|i, _, pixel| {
+                let i = i as i32;
+                let pos = (min + IVec2::new(i % width, i / width)).rem_euclid(IVec2::splat(4));
 
-    draw_animation(spatial, param, &mut image, animation, filters);
+                if !dither(dithering, pos.as_uvec2()) {
+                    *pixel = None;
+                }
+            });
+
+            image.draw(faded);
+        }
+        _ => draw_animation(spatial, param, &mut image, animation, filters),
+    }
 }
 
 #[derive(Resource)]
@@ -364,3 +552,51 @@ fn finish_animations<A: AnimatedAssetComponent>(
         }
     }
 }
+
+#[cfg(all(test, feature = "state"))]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use crate::position::InsertDefaultLayer;
+
+    use super::*;
+
+    #[px_layer]
+    struct Layer;
+
+    #[test]
+    fn done_animation_succeeds() {
+        let mut world = World::new();
+        let mut time = Time::<Real>::default();
+        time.update();
+        let now = time.last_update().unwrap();
+        world.insert_resource(time);
+        world.insert_resource(InsertDefaultLayer::new::<Layer>());
+        world.init_resource::<Assets<PxSpriteAsset>>();
+
+        let sprite = world
+            .resource_mut::<Assets<PxSpriteAsset>>()
+            .add(PxSpriteAsset {
+                data: PxImage::new(vec![Some(0); 2], 1),
+                frame_size: 1,
+                trim: None,
+            });
+        let entity = world
+            .spawn((
+                PxSprite(sprite),
+                PxAnimation {
+                    duration: PxAnimationDuration::millis_per_frame(100),
+                    on_finish: PxAnimationFinishBehavior::Done,
+                    start: now - Duration::from_secs(1),
+                    ..default()
+                },
+            ))
+            .id();
+
+        world
+            .run_system_once(finish_animations::<PxSprite>)
+            .unwrap();
+
+        assert!(matches!(world.get::<Done>(entity), Some(Done::Success)));
+    }
+}
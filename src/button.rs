@@ -119,6 +119,7 @@ fn interact_buttons(
         &PxPosition,
         &PxInteractBounds,
         &PxAnchor,
+        Option<&PxAnchorOffset>,
         &PxCanvas,
         Option<&PxHover>,
         Option<&PxClick>,
@@ -127,18 +128,17 @@ fn interact_buttons(
     mouse: Res<ButtonInput<MouseButton>>,
     camera: Res<PxCamera>,
 ) {
-    for (button, position, bounds, anchor, canvas, hovered, clicked) in &buttons {
+    for (button, position, bounds, anchor, anchor_offset, canvas, hovered, clicked) in &buttons {
         let mut button = commands.entity(button);
 
         if let Some(cursor_pos) = **cursor_pos {
-            let cursor_pos = cursor_pos.as_ivec2();
-            let cursor_pos = match canvas {
-                PxCanvas::World => cursor_pos + **camera,
-                PxCanvas::Camera => cursor_pos,
-            };
+            let cursor_pos = camera.screen_to_world(cursor_pos.as_ivec2(), *canvas);
+
+            let offset =
+                bounds.offset.as_ivec2() + anchor_offset.map_or(IVec2::ZERO, |offset| **offset);
 
             if IRect::pos_size_anchor(**position, bounds.size, *anchor)
-                .contains_exclusive(cursor_pos - bounds.offset.as_ivec2())
+                .contains_exclusive(cursor_pos - offset)
             {
                 if hovered.is_none() {
                     button.insert(PxHover);
@@ -1,19 +1,80 @@
 use bevy::render::{
-    extract_component::ExtractComponent,
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
     extract_resource::{ExtractResource, ExtractResourcePlugin},
 };
 
-use crate::prelude::*;
+use crate::{prelude::*, screen::Screen, set::PxSet};
 
 pub(crate) fn plug(app: &mut App) {
-    app.add_plugins(ExtractResourcePlugin::<PxCamera>::default())
-        .init_resource::<PxCamera>();
+    app.add_plugins((
+        ExtractResourcePlugin::<PxCamera>::default(),
+        ExtractResourcePlugin::<CameraShakeOffset>::default(),
+        ExtractComponentPlugin::<PxViewportCamera>::default(),
+    ))
+    .init_resource::<PxCamera>()
+    .init_resource::<CameraShakeOffset>()
+    .add_systems(
+        PostUpdate,
+        (
+            follow_camera
+                .run_if(resource_exists::<PxCameraFollow>)
+                .run_if(resource_exists::<Screen>),
+            clamp_camera
+                .run_if(resource_exists::<PxCameraBounds>)
+                .run_if(resource_exists::<Screen>),
+            shake_camera,
+        )
+            .chain()
+            .in_set(PxSet::UpdateCamera),
+    );
 }
 
 /// Resource that represents the camera's position
 #[derive(ExtractResource, Resource, Deref, DerefMut, Clone, Copy, Default, Debug)]
 pub struct PxCamera(pub IVec2);
 
+impl PxCamera {
+    /// Converts a position on the given canvas to a position on the screen,
+    /// in pixels from the bottom-left
+    pub fn world_to_screen(self, position: IVec2, canvas: PxCanvas) -> IVec2 {
+        match canvas {
+            PxCanvas::World => position - *self,
+            PxCanvas::Camera => position,
+        }
+    }
+
+    /// Converts a position on the screen, in pixels from the bottom-left,
+    /// to a position on the given canvas
+    pub fn screen_to_world(self, position: IVec2, canvas: PxCanvas) -> IVec2 {
+        match canvas {
+            PxCanvas::World => position + *self,
+            PxCanvas::Camera => position,
+        }
+    }
+}
+
+/// Gives a `Camera2d` its own pixel camera position, used instead of [`PxCamera`] when
+/// rendering that view. Combined with `Camera::viewport`, this allows split-screen.
+/// Camera shake still applies to every view.
+///
+/// Limitations:
+/// - The whole screen is drawn into each viewport, scaled as if it filled the window,
+///   so viewports should match the window's aspect ratio to avoid stretching
+/// - Inside a viewport, the letterbox is the camera's clear color, not [`PxLetterboxColor`]
+/// - Give cameras after the first `ClearColorConfig::None`, or they will clear earlier views
+/// - The cursor and [`PxCamera`]'s coordinate conversions are relative to the window
+///   and [`PxCamera`]
+/// - Has no effect with [`PxImageTarget`]
+/// - The screen is composed once per view, so each extra view costs another composition
+#[derive(ExtractComponent, Component, Deref, DerefMut, Clone, Copy, Default, Debug)]
+pub struct PxViewportCamera(pub IVec2);
+
+impl From<IVec2> for PxViewportCamera {
+    fn from(position: IVec2) -> Self {
+        Self(position)
+    }
+}
+
 /// Determines whether the entity is locked to the camera
 #[derive(ExtractComponent, Component, Clone, Copy, Default, Debug)]
 pub enum PxCanvas {
@@ -23,3 +84,170 @@ pub enum PxCanvas {
     /// The entity is drawn relative to the camera, like UI
     Camera,
 }
+
+/// Scrolls a sprite or map at a fraction of the camera's speed, for parallax backgrounds.
+/// `1` on an axis scrolls like any other entity on [`PxCanvas::World`], and `0` doesn't scroll
+/// at all. Has no effect on [`PxCanvas::Camera`].
+#[derive(Component, Deref, DerefMut, Clone, Copy, Debug)]
+pub struct PxParallax(pub Vec2);
+
+impl Default for PxParallax {
+    fn default() -> Self {
+        Self(Vec2::ONE)
+    }
+}
+
+impl From<Vec2> for PxParallax {
+    fn from(factor: Vec2) -> Self {
+        Self(factor)
+    }
+}
+
+impl PxParallax {
+    pub(crate) fn camera(self, camera: PxCamera) -> PxCamera {
+        PxCamera((camera.as_vec2() * *self).round().as_ivec2())
+    }
+}
+
+/// Resource that makes [`PxCamera`] follow an entity's [`PxSubPosition`],
+/// or its [`PxPosition`] if it doesn't have one
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct PxCameraFollow {
+    /// The entity to follow
+    pub target: Entity,
+    /// How quickly the camera catches up to the target. Higher values are snappier.
+    /// Use [`f32::INFINITY`] to disable smoothing.
+    pub lerp: f32,
+    /// Area that the target can move in without moving the camera,
+    /// relative to the center of the screen
+    pub deadzone: IRect,
+}
+
+/// Resource that keeps [`PxCamera`] from showing anything outside of the contained rectangle.
+/// The rectangle's `max` is exclusive. If the rectangle is smaller than the screen
+/// on an axis, the camera is centered on it on that axis.
+#[derive(Resource, Deref, DerefMut, Clone, Copy, Debug)]
+pub struct PxCameraBounds(pub IRect);
+
+/// Resource that shakes the rendered camera by a random offset each frame, without changing
+/// [`PxCamera`]. Set `amplitude` to start shaking.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct PxCameraShake {
+    /// Maximum offset of the camera on each axis, in pixels
+    pub amplitude: f32,
+    /// How quickly `amplitude` decays. Higher values stop shaking sooner.
+    pub decay: f32,
+}
+
+impl Default for PxCameraShake {
+    fn default() -> Self {
+        Self {
+            amplitude: 0.,
+            decay: 5.,
+        }
+    }
+}
+
+#[derive(ExtractResource, Resource, Deref, Clone, Copy, Default, Debug)]
+pub(crate) struct CameraShakeOffset(IVec2);
+
+impl PxCameraFollow {
+    /// Create a [`PxCameraFollow`] that keeps the target centered without smoothing
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            lerp: f32::INFINITY,
+            deadzone: default(),
+        }
+    }
+}
+
+fn follow_camera(
+    follow: Res<PxCameraFollow>,
+    targets: Query<(&PxPosition, Option<&PxSubPosition>)>,
+    screen: Res<Screen>,
+    time: Res<Time>,
+    mut camera: ResMut<PxCamera>,
+    mut sub_camera: Local<Option<Vec2>>,
+) {
+    let Ok((position, sub_position)) = targets.get(follow.target) else {
+        return;
+    };
+
+    let target = sub_position.map_or(position.as_vec2(), |sub_position| **sub_position);
+
+    // If something else moved the camera, start from there
+    let position = match *sub_camera {
+        Some(sub_camera) if sub_camera.round().as_ivec2() == **camera => sub_camera,
+        _ => camera.as_vec2(),
+    };
+
+    let center = position + screen.size().as_vec2() / 2.;
+    let offset =
+        (target - center).clamp(follow.deadzone.min.as_vec2(), follow.deadzone.max.as_vec2());
+    let smoothing = if follow.lerp.is_finite() {
+        1. - (-follow.lerp * time.delta_secs()).exp()
+    } else {
+        1.
+    };
+    let position = position + (target - offset - center) * smoothing;
+
+    *sub_camera = Some(position);
+    **camera = position.round().as_ivec2();
+}
+
+fn clamp_camera(bounds: Res<PxCameraBounds>, screen: Res<Screen>, mut camera: ResMut<PxCamera>) {
+    let size = screen.size().as_ivec2();
+    let max = bounds.max - size;
+
+    let clamp = |camera: i32, min: i32, max: i32| {
+        if max < min {
+            (min + max) / 2
+        } else {
+            camera.clamp(min, max)
+        }
+    };
+
+    let clamped = IVec2::new(
+        clamp(camera.x, bounds.min.x, max.x),
+        clamp(camera.y, bounds.min.y, max.y),
+    );
+
+    if clamped != **camera {
+        **camera = clamped;
+    }
+}
+
+fn shake_camera(
+    shake: Option<ResMut<PxCameraShake>>,
+    time: Res<Time>,
+    mut offset: ResMut<CameraShakeOffset>,
+    mut seed: Local<u32>,
+) {
+    let Some(mut shake) = shake.filter(|shake| shake.amplitude > 0.) else {
+        if **offset != IVec2::ZERO {
+            *offset = default();
+        }
+
+        return;
+    };
+
+    // Xorshift, since the shake doesn't need a good RNG,
+    // and `bevy_turborand` is only available with the `particle` feature
+    let mut random = || {
+        *seed = seed.max(1);
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 17;
+        *seed ^= *seed << 5;
+        *seed as f32 / u32::MAX as f32 * 2. - 1.
+    };
+
+    offset.0 = (Vec2::new(random(), random()) * shake.amplitude)
+        .round()
+        .as_ivec2();
+    shake.amplitude *= (-shake.decay * time.delta_secs()).exp();
+
+    if shake.amplitude < 0.5 {
+        shake.amplitude = 0.;
+    }
+}
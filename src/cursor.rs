@@ -5,12 +5,7 @@ use bevy::{
     window::PrimaryWindow,
 };
 
-use crate::{
-    filter::PxFilterAsset,
-    prelude::*,
-    screen::{screen_scale, Screen},
-    set::PxSet,
-};
+use crate::{filter::PxFilterAsset, prelude::*, screen::Screen, set::PxSet};
 
 pub(crate) fn plug(app: &mut App) {
     app.add_plugins((
@@ -20,9 +15,20 @@ pub(crate) fn plug(app: &mut App) {
     ))
     .init_resource::<PxCursor>()
     .init_resource::<PxCursorPosition>()
+    .init_resource::<PxCursorWorldPosition>()
     .add_systems(
         PreUpdate,
-        update_cursor_position.in_set(PxSet::UpdateCursorPosition),
+        (
+            (
+                update_cursor_position,
+                move_gamepad_cursor.run_if(resource_exists::<PxGamepadCursor>),
+            )
+                .chain()
+                .run_if(resource_exists::<Screen>),
+            update_cursor_world_position,
+        )
+            .chain()
+            .in_set(PxSet::UpdateCursorPosition),
     )
     .add_systems(PostUpdate, change_cursor);
 }
@@ -43,6 +49,19 @@ pub enum PxCursor {
         /// Filter to use when right clicking
         right_click: Handle<PxFilterAsset>,
     },
+    /// Use an in-game sprite cursor, drawn over everything else
+    Sprite {
+        /// Sprite to use when not clicking
+        idle: Handle<PxSpriteAsset>,
+        /// Sprite to use when left clicking
+        left_click: Handle<PxSpriteAsset>,
+        /// Sprite to use when right clicking
+        right_click: Handle<PxSpriteAsset>,
+        /// The sprite's click point, in pixels from its top-left. This pixel is drawn
+        /// at [`PxCursorPosition`], so it is the pixel that interacts with buttons.
+        /// Use [`UVec2::ZERO`] for the top-left pixel.
+        hotspot: UVec2,
+    },
 }
 
 /// Resource marking the cursor's position. Measured in pixels from the bottom-left of the screen.
@@ -51,10 +70,17 @@ pub enum PxCursor {
 #[derive(ExtractResource, Resource, Deref, DerefMut, Clone, Default, Debug)]
 pub struct PxCursorPosition(pub Option<UVec2>);
 
+/// Resource marking the cursor's world position, which is [`PxCursorPosition`]
+/// plus [`PxCamera`]. Compare this to the positions of entities on [`PxCanvas::World`],
+/// and [`PxCursorPosition`] to those on [`PxCanvas::Camera`]. Contains [`None`]
+/// if the cursor is off-screen. Updated in [`PxSet::UpdateCursorPosition`], so changes
+/// to [`PxCamera`] are reflected on the next frame.
+#[derive(Resource, Deref, DerefMut, Clone, Copy, Default, Debug)]
+pub struct PxCursorWorldPosition(pub Option<IVec2>);
+
 fn update_cursor_position(
     mut move_events: EventReader<CursorMoved>,
     mut leave_events: EventReader<CursorLeft>,
-    cameras: Query<(&Camera, &GlobalTransform)>,
     screen: Res<Screen>,
     mut position: ResMut<PxCursorPosition>,
     windows: Query<&Window>,
@@ -68,30 +94,58 @@ fn update_cursor_position(
         return;
     };
 
-    let Ok((camera, tf)) = cameras.get_single() else {
-        return;
-    };
-
     let Ok(window) = windows.get_single() else {
         return;
     };
 
-    let Ok(new_position) = camera.viewport_to_world_2d(tf, event.position) else {
-        **position = None;
+    **position = screen.window_to_screen(window, event.position);
+}
+
+/// Resource that lets gamepads move the cursor with their left stick. Use it
+/// with an in-game cursor, since the operating system's cursor doesn't follow it.
+/// The mouse still moves the cursor too, and whichever moved last wins.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct PxGamepadCursor {
+    /// Speed of the cursor at full tilt, in pixels per second
+    pub speed: f32,
+}
+
+fn move_gamepad_cursor(
+    mut sub_position: Local<Vec2>,
+    cursor: Res<PxGamepadCursor>,
+    gamepads: Query<&Gamepad>,
+    screen: Res<Screen>,
+    time: Res<Time>,
+    mut position: ResMut<PxCursorPosition>,
+) {
+    let Some(stick) = gamepads
+        .iter()
+        .map(Gamepad::left_stick)
+        .find(|&stick| stick != Vec2::ZERO)
+    else {
         return;
     };
 
-    let new_position = new_position
-        / screen_scale(
-            screen.computed_size,
-            Vec2::new(window.width(), window.height()),
-        )
-        * screen.computed_size.as_vec2()
-        + screen.computed_size.as_vec2() / 2.;
+    let screen_size = screen.computed_size.as_vec2();
+    let current_position = position.map_or(screen_size / 2., |position| position.as_vec2());
 
-    **position = (new_position.cmpge(Vec2::ZERO).all()
-        && new_position.cmplt(screen.computed_size.as_vec2()).all())
-    .then(|| new_position.as_uvec2());
+    // The mouse moved the cursor since the last time the gamepad did
+    if sub_position.floor() != current_position {
+        *sub_position = current_position;
+    }
+
+    *sub_position = (*sub_position + stick * cursor.speed * time.delta_secs())
+        .clamp(Vec2::ZERO, screen_size - 1.);
+    **position = Some(sub_position.as_uvec2());
+}
+
+fn update_cursor_world_position(
+    position: Res<PxCursorPosition>,
+    camera: Res<PxCamera>,
+    mut world_position: ResMut<PxCursorWorldPosition>,
+) {
+    **world_position =
+        position.map(|position| camera.screen_to_world(position.as_ivec2(), PxCanvas::World));
 }
 
 fn change_cursor(
@@ -110,7 +164,7 @@ fn change_cursor(
     window.cursor_options.visible = cursor_pos.is_none()
         || match *cursor {
             PxCursor::Os => true,
-            PxCursor::Filter { .. } => false,
+            PxCursor::Filter { .. } | PxCursor::Sprite { .. } => false,
         };
 }
 
@@ -0,0 +1,157 @@
+//! Dithering, for converting full-color images to palette indices
+
+use anyhow::{anyhow, Result};
+use bevy::render::render_resource::TextureFormat;
+
+use crate::{palette::Palette, prelude::*};
+
+/// Dithering to use when converting full-color images to palette indices
+#[derive(Clone, Copy, Debug)]
+pub struct PxDither {
+    /// The dithering algorithm
+    pub algorithm: PxDitherAlgorithm,
+    /// How much to dither, from 0 (not at all) to 1
+    pub threshold: f32,
+}
+
+/// A dithering algorithm
+#[derive(Clone, Copy, Debug, Default)]
+pub enum PxDitherAlgorithm {
+    /// Ordered dithering with a 4x4 Bayer matrix
+    #[default]
+    Ordered,
+    /// Floyd-Steinberg error diffusion. Smoother than ordered dithering,
+    /// but it must process the image serially.
+    FloydSteinberg,
+    /// Atkinson error diffusion. Like Floyd-Steinberg, but only diffuses part of the error,
+    /// which preserves contrast.
+    Atkinson,
+}
+
+impl PxDitherAlgorithm {
+    // Where error diffusion spreads each pixel's error, as (x offset, y offset down, weight)
+    fn diffusion(self) -> &'static [(isize, usize, f32)] {
+        match self {
+            PxDitherAlgorithm::Ordered => &[],
+            PxDitherAlgorithm::FloydSteinberg => &[
+                (1, 0, 7. / 16.),
+                (-1, 1, 3. / 16.),
+                (0, 1, 5. / 16.),
+                (1, 1, 1. / 16.),
+            ],
+            PxDitherAlgorithm::Atkinson => &[
+                (1, 0, 1. / 8.),
+                (2, 0, 1. / 8.),
+                (-1, 1, 1. / 8.),
+                (0, 1, 1. / 8.),
+                (1, 1, 1. / 8.),
+                (0, 2, 1. / 8.),
+            ],
+        }
+    }
+}
+
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// Converts an image to palette indices, in rows from top to bottom. Pixels with less than half
+// alpha are transparent. Other pixels are mapped to the nearest color in the palette.
+pub(crate) fn dither_image(
+    image: &Image,
+    palette: &Palette,
+    dither: Option<PxDither>,
+) -> Result<Vec<Option<u8>>> {
+    let image = image
+        .convert(TextureFormat::Rgba8UnormSrgb)
+        .ok_or_else(|| anyhow!("could not convert image to `Rgba8UnormSrgb`"))?;
+    let width = image.texture_descriptor.size.width as usize;
+
+    if let Some(PxDither {
+        algorithm: algorithm @ (PxDitherAlgorithm::FloydSteinberg | PxDitherAlgorithm::Atkinson),
+        threshold,
+    }) = dither
+    {
+        return Ok(diffuse_error(
+            &image.data,
+            width,
+            palette,
+            algorithm,
+            threshold,
+        ));
+    }
+
+    Ok(image
+        .data
+        .chunks_exact(4)
+        .enumerate()
+        .map(|(i, color)| {
+            if color[3] < 128 {
+                return None;
+            }
+
+            let color = Vec3::new(color[0] as f32, color[1] as f32, color[2] as f32) / 255.;
+            let offset = match dither {
+                Some(PxDither {
+                    algorithm: PxDitherAlgorithm::Ordered,
+                    threshold,
+                }) => (BAYER_4X4[i / width % 4][i % width % 4] as f32 / 16. - 0.5) * threshold,
+                Some(_) | None => 0.,
+            };
+
+            Some(nearest(palette, color + offset))
+        })
+        .collect())
+}
+
+// Error diffusion carries each pixel's error to later pixels, so it runs serially
+fn diffuse_error(
+    data: &[u8],
+    width: usize,
+    palette: &Palette,
+    algorithm: PxDitherAlgorithm,
+    threshold: f32,
+) -> Vec<Option<u8>> {
+    let mut colors = data
+        .chunks_exact(4)
+        .map(|color| {
+            (color[3] >= 128)
+                .then(|| Vec3::new(color[0] as f32, color[1] as f32, color[2] as f32) / 255.)
+        })
+        .collect::<Vec<_>>();
+    let height = colors.len() / width;
+    let mut indices = Vec::with_capacity(colors.len());
+
+    for i in 0..colors.len() {
+        let Some(color) = colors[i] else {
+            indices.push(None);
+            continue;
+        };
+
+        let index = nearest(palette, color);
+        indices.push(Some(index));
+
+        let [r, g, b] = palette.color(index);
+        let error = (color - Vec3::new(r as f32, g as f32, b as f32) / 255.) * threshold;
+        let (x, y) = (i % width, i / width);
+
+        for &(dx, dy, weight) in algorithm.diffusion() {
+            let Some(x) = x.checked_add_signed(dx).filter(|&x| x < width) else {
+                continue;
+            };
+
+            if y + dy >= height {
+                continue;
+            }
+
+            if let Some(neighbor) = &mut colors[x + (y + dy) * width] {
+                *neighbor += error * weight;
+            }
+        }
+    }
+
+    indices
+}
+
+fn nearest(palette: &Palette, color: Vec3) -> u8 {
+    let color = color.clamp(Vec3::ZERO, Vec3::ONE);
+    palette.nearest(Color::srgb(color.x, color.y, color.z))
+}
@@ -0,0 +1,87 @@
+//! Custom drawables
+
+use std::any::TypeId;
+
+use bevy::{
+    render::{Extract, RenderApp},
+    utils::HashMap,
+};
+
+use crate::{image::PxImage, position::PxLayer, prelude::*};
+
+pub(crate) fn plug<L: PxLayer>(app: &mut App) {
+    app.sub_app_mut(RenderApp)
+        .init_resource::<ExtractedDrawables<L>>();
+}
+
+/// A component that draws itself onto its layer. Register it with
+/// [`PxDrawableAppExt::add_px_drawable`]. Entities with a drawable are drawn on their layer
+/// at their [`PxPosition`], relative to their [`PxCanvas`], after the layer's sprites, text,
+/// and lines, and before its filters.
+pub trait PxDrawable: Component + Clone {
+    /// Draws onto the layer's image. `position` is the entity's position on the screen,
+    /// in pixels from the bottom-left. [`PxImage`] is indexed with y down, so a pixel at `y`
+    /// pixels from the bottom is at `image.height() - 1 - y`.
+    fn draw(&self, image: &mut PxImage<Option<u8>>, position: IVec2);
+}
+
+/// Extension trait for registering [`PxDrawable`]s
+pub trait PxDrawableAppExt {
+    /// Registers a [`PxDrawable`], so it's drawn on layers of type `L`
+    fn add_px_drawable<D: PxDrawable, L: PxLayer>(&mut self) -> &mut Self;
+}
+
+impl PxDrawableAppExt for App {
+    fn add_px_drawable<D: PxDrawable, L: PxLayer>(&mut self) -> &mut Self {
+        self.sub_app_mut(RenderApp)
+            .add_systems(ExtractSchedule, extract_drawables::<D, L>);
+        self
+    }
+}
+
+pub(crate) type DrawFn = Box<dyn Fn(&mut PxImage<Option<u8>>, PxCamera) + Send + Sync>;
+
+// Keyed by the drawable's type, so each extraction system replaces only its own drawables
+#[derive(Resource, Deref)]
+pub(crate) struct ExtractedDrawables<L: PxLayer>(pub(crate) HashMap<TypeId, Vec<(L, DrawFn)>>);
+
+impl<L: PxLayer> Default for ExtractedDrawables<L> {
+    fn default() -> Self {
+        Self(default())
+    }
+}
+
+fn extract_drawables<D: PxDrawable, L: PxLayer>(
+    drawables: Extract<
+        Query<
+            (
+                &D,
+                &PxPosition,
+                &L,
+                Option<&PxCanvas>,
+                Option<&InheritedVisibility>,
+            ),
+            Without<PxHidden>,
+        >,
+    >,
+    mut extracted: ResMut<ExtractedDrawables<L>>,
+) {
+    extracted.0.insert(
+        TypeId::of::<D>(),
+        drawables
+            .iter()
+            .filter(|(_, _, _, _, visibility)| visibility.is_none_or(|visibility| visibility.get()))
+            .map(|(drawable, &position, layer, canvas, _)| {
+                let drawable = drawable.clone();
+                let canvas = canvas.copied().unwrap_or_default();
+
+                (
+                    layer.clone(),
+                    Box::new(move |image: &mut PxImage<Option<u8>>, camera: PxCamera| {
+                        drawable.draw(image, camera.world_to_screen(*position, canvas));
+                    }) as DrawFn,
+                )
+            })
+            .collect(),
+    );
+}
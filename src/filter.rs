@@ -1,24 +1,27 @@
 //! Filtering
 
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
-use anyhow::{Error, Result};
+use anyhow::{anyhow, Error, Result};
 use bevy::{
     asset::{io::Reader, AssetLoader, LoadContext},
     ecs::{component::ComponentId, world::DeferredWorld},
-    image::{CompressedImageFormats, ImageLoader, ImageLoaderSettings},
+    image::{
+        CompressedImageFormats, ImageFormatSetting, ImageLoader, ImageLoaderSettings, ImageSampler,
+    },
     render::{
-        render_asset::{PrepareAssetError, RenderAsset, RenderAssetPlugin},
+        render_asset::{PrepareAssetError, RenderAsset, RenderAssetPlugin, RenderAssetUsages},
         sync_component::SyncComponentPlugin,
         sync_world::RenderEntity,
         Extract, RenderApp,
     },
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
     animation::{draw_animation, AnimatedAssetComponent, Animation, PxAnimation},
-    image::{PxImage, PxImageSliceMut},
-    palette::asset_palette,
+    image::{decode_png, PxImage, PxImageSliceMut},
+    palette::{asset_palette, Palette},
     pixel::Pixel,
     position::PxLayer,
     prelude::*,
@@ -32,77 +35,146 @@ pub(crate) fn plug<L: PxLayer>(app: &mut App) {
     .init_asset::<PxFilterAsset>()
     .init_asset_loader::<PxFilterLoader>()
     .insert_resource(InsertDefaultPxFilterLayers::new::<L>())
+    .add_systems(PostUpdate, bake_runtime_filters)
     .sub_app_mut(RenderApp)
     .insert_resource(InsertDefaultPxFilterLayers::new::<L>())
     .add_systems(ExtractSchedule, extract_filters::<L>);
 }
 
+// Keeps `ImageLoaderSettings`'s fields at the top level, since this loader's settings
+// used to be `ImageLoaderSettings`, so existing `.meta` files still load
+#[derive(Serialize, Deserialize)]
+struct PxFilterLoaderSettings {
+    format: ImageFormatSetting,
+    is_srgb: bool,
+    sampler: ImageSampler,
+    asset_usage: RenderAssetUsages,
+    // Color that is loaded as transparent, for filters that use a color key instead of alpha
+    #[serde(default)]
+    transparent_color: Option<[u8; 3]>,
+}
+
+impl Default for PxFilterLoaderSettings {
+    fn default() -> Self {
+        let ImageLoaderSettings {
+            format,
+            is_srgb,
+            sampler,
+            asset_usage,
+        } = default();
+
+        Self {
+            format,
+            is_srgb,
+            sampler,
+            asset_usage,
+            transparent_color: None,
+        }
+    }
+}
+
+impl PxFilterLoaderSettings {
+    fn image_loader_settings(&self) -> ImageLoaderSettings {
+        ImageLoaderSettings {
+            format: match self.format {
+                ImageFormatSetting::FromExtension => ImageFormatSetting::FromExtension,
+                ImageFormatSetting::Format(format) => ImageFormatSetting::Format(format),
+                ImageFormatSetting::Guess => ImageFormatSetting::Guess,
+            },
+            is_srgb: self.is_srgb,
+            sampler: self.sampler.clone(),
+            asset_usage: self.asset_usage,
+        }
+    }
+}
+
 #[derive(Default)]
 struct PxFilterLoader;
 
 impl AssetLoader for PxFilterLoader {
     type Asset = PxFilterAsset;
-    type Settings = ImageLoaderSettings;
+    type Settings = PxFilterLoaderSettings;
     type Error = Error;
 
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        settings: &ImageLoaderSettings,
+        settings: &PxFilterLoaderSettings,
         load_context: &mut LoadContext<'_>,
     ) -> Result<PxFilterAsset> {
         let image = ImageLoader::new(CompressedImageFormats::NONE)
-            .load(reader, settings, load_context)
+            .load(reader, &settings.image_loader_settings(), load_context)
             .await?;
-        let palette = asset_palette().await;
-        let indices = PxImage::palette_indices(palette, &image)?;
-
-        let mut filter = Vec::with_capacity(indices.area());
-        let frame_size = palette.size;
-        let frame_area = frame_size.x * frame_size.y;
-        let filter_width = image.texture_descriptor.size.width;
-        let frame_filter_width = filter_width / palette.size.x;
-
-        let mut frame_visible = true;
-
-        for i in 0..indices.area() {
-            let frame_index = i as u32 / frame_area;
-            let frame_pos = i as u32 % frame_area;
-
-            if frame_pos == 0 {
-                if !frame_visible {
-                    for _ in 0..frame_area {
-                        filter.pop();
-                    }
-                    break;
-                }
 
-                frame_visible = false;
+        load_filter(&image, asset_palette().await, settings.transparent_color)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["px_filter.png"]
+    }
+}
+
+fn load_filter(
+    image: &Image,
+    palette: &Palette,
+    transparent_color: Option<[u8; 3]>,
+) -> Result<PxFilterAsset> {
+    let size = image.size();
+    if palette.size.x == 0
+        || palette.size.y == 0
+        || size.x < palette.size.x
+        || !size.x.is_multiple_of(palette.size.x)
+        || !size.y.is_multiple_of(palette.size.y)
+    {
+        return Err(anyhow!(
+            "filter's size of {size} isn't a multiple of the palette's size of {}",
+            palette.size
+        ));
+    }
+
+    let indices = PxImage::palette_indices(palette, image, transparent_color)?;
+
+    let mut filter = Vec::with_capacity(indices.area());
+    let frame_size = palette.size;
+    let frame_area = frame_size.x * frame_size.y;
+    let filter_width = image.texture_descriptor.size.width;
+    let frame_filter_width = filter_width / palette.size.x;
+
+    let mut frame_visible = true;
+
+    for i in 0..indices.area() {
+        let frame_index = i as u32 / frame_area;
+        let frame_pos = i as u32 % frame_area;
+
+        if frame_pos == 0 {
+            if !frame_visible {
+                for _ in 0..frame_area {
+                    filter.pop();
+                }
+                break;
             }
 
-            filter.push(
-                if let Some(index) = indices.pixel(
-                    (UVec2::new(
-                        frame_index % frame_filter_width,
-                        frame_index / frame_filter_width,
-                    ) * frame_size
-                        + UVec2::new(frame_pos % frame_size.x, frame_pos / frame_size.x))
-                    .as_ivec2(),
-                ) {
-                    frame_visible = true;
-                    index
-                } else {
-                    0
-                },
-            );
+            frame_visible = false;
         }
 
-        Ok(PxFilterAsset(PxImage::new(filter, frame_area as usize)))
+        filter.push(
+            if let Some(index) = indices.pixel(
+                (UVec2::new(
+                    frame_index % frame_filter_width,
+                    frame_index / frame_filter_width,
+                ) * frame_size
+                    + UVec2::new(frame_pos % frame_size.x, frame_pos / frame_size.x))
+                .as_ivec2(),
+            ) {
+                frame_visible = true;
+                index
+            } else {
+                0
+            },
+        );
     }
 
-    fn extensions(&self) -> &[&str] {
-        &["px_filter.png"]
-    }
+    Ok(PxFilterAsset(PxImage::new(filter, frame_area as usize)))
 }
 
 /// Maps colors of an image to different colors. Filter a single sprite, text, or tilemap
@@ -142,8 +214,8 @@ impl Animation for PxFilterAsset {
         &self,
         (): (),
         image: &mut PxImageSliceMut<impl Pixel>,
-        frame: impl Fn(UVec2) -> usize,
-        _: impl Fn(u8) -> u8,
+        frame: impl Fn(UVec2) -> usize + Sync,
+        _: impl Fn(u8) -> u8 + Sync,
     ) {
         let Self(filter) = self;
         let width = image.width();
@@ -160,6 +232,12 @@ impl Animation for PxFilterAsset {
 }
 
 impl PxFilterAsset {
+    /// Creates a filter from the bytes of a PNG, such as one embedded with [`include_bytes!`],
+    /// without the asset server
+    pub fn from_png_bytes(bytes: &[u8], palette: &Palette) -> Result<Self> {
+        load_filter(&decode_png(bytes)?, palette, None)
+    }
+
     pub(crate) fn as_fn(&self) -> impl '_ + Fn(u8) -> u8 {
         let Self(filter) = self;
         |pixel| filter.pixel(IVec2::new(pixel as i32, 0))
@@ -182,6 +260,34 @@ impl AnimatedAssetComponent for PxFilter {
     }
 }
 
+/// Applies a filter computed by a function from palette index to palette index, such as
+/// a simple programmatic remap that would be tedious to author as a [`PxFilterAsset`].
+/// The function is baked into a single-frame [`PxFilterAsset`] whenever this component
+/// is added or changed, replacing the entity's [`PxFilter`], so it works anywhere
+/// [`PxFilter`] does, including with [`PxFilterLayers`]. It's called once for every index,
+/// so keep it cheap if you change this component often.
+#[derive(Component, Deref, DerefMut, Clone)]
+pub struct PxRuntimeFilter(pub Arc<dyn Fn(u8) -> u8 + Send + Sync>);
+
+impl<T: 'static + Fn(u8) -> u8 + Send + Sync> From<T> for PxRuntimeFilter {
+    fn from(t: T) -> Self {
+        Self(Arc::new(t))
+    }
+}
+
+fn bake_runtime_filters(
+    mut commands: Commands,
+    filters: Query<(Entity, &PxRuntimeFilter), Changed<PxRuntimeFilter>>,
+    mut filter_assets: ResMut<Assets<PxFilterAsset>>,
+) {
+    for (entity, PxRuntimeFilter(remap)) in &filters {
+        let filter = PxFilterAsset(PxImage::new((0..=u8::MAX).map(&**remap).collect(), 256));
+        commands
+            .entity(entity)
+            .insert(PxFilter(filter_assets.add(filter)));
+    }
+}
+
 /// Function that can be used as a layer selection function in `PxFilterLayers`. Automatically
 /// implemented for types with the bounds and `Clone`.
 pub trait SelectLayerFn<L: PxLayer>: 'static + Fn(&L) -> bool + Send + Sync {
@@ -279,17 +385,27 @@ pub(crate) type FilterComponents<L> = (
 
 fn extract_filters<L: PxLayer>(
     filters: Extract<
-        Query<(FilterComponents<L>, &InheritedVisibility, RenderEntity), Without<PxCanvas>>,
+        Query<
+            (
+                FilterComponents<L>,
+                &InheritedVisibility,
+                Has<PxHidden>,
+                RenderEntity,
+            ),
+            Without<PxCanvas>,
+        >,
     >,
     mut cmd: Commands,
 ) {
-    for ((filter, layers, animation), visibility, id) in &filters {
-        if !visibility.get() {
+    for ((filter, layers, animation), visibility, hidden, id) in &filters {
+        if hidden || !visibility.get() {
+            cmd.entity(id).insert(PxHidden);
             continue;
         }
 
         let mut entity = cmd.entity(id);
         entity.insert((filter.clone(), layers.clone()));
+        entity.remove::<PxHidden>();
 
         if let Some(animation) = animation {
             entity.insert(*animation);
@@ -312,3 +428,36 @@ pub(crate) fn draw_filter(
 ) {
     draw_animation(filter, (), image, animation, []);
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::ron;
+
+    use super::*;
+
+    #[test]
+    fn loader_settings_accept_image_loader_settings() {
+        let settings = ron::from_str::<PxFilterLoaderSettings>(
+            r#"(
+                format: FromExtension,
+                is_srgb: true,
+                sampler: Default,
+                asset_usage: ("MAIN_WORLD | RENDER_WORLD"),
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(settings.transparent_color, None);
+
+        let settings = ron::from_str::<PxFilterLoaderSettings>(
+            r#"(
+                format: FromExtension,
+                is_srgb: true,
+                sampler: Default,
+                asset_usage: ("MAIN_WORLD | RENDER_WORLD"),
+                transparent_color: Some((255, 0, 255)),
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(settings.transparent_color, Some([255, 0, 255]));
+    }
+}
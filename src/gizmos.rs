@@ -0,0 +1,122 @@
+//! Immediate-mode debug drawing
+
+use std::any::TypeId;
+
+use bevy::{
+    ecs::system::SystemParam,
+    render::{Extract, RenderApp},
+    utils::HashSet,
+};
+
+use crate::{
+    drawable::{DrawFn, ExtractedDrawables},
+    image::PxImage,
+    line::stroke,
+    position::PxLayer,
+    prelude::*,
+};
+
+pub(crate) fn plug<L: PxLayer>(app: &mut App) {
+    app.init_resource::<GizmoBuffer<L>>()
+        .add_systems(First, clear_gizmos::<L>)
+        .sub_app_mut(RenderApp)
+        .add_systems(ExtractSchedule, extract_gizmos::<L>);
+}
+
+// Each gizmo is a layer, a palette index, and line segments
+#[derive(Resource, Deref, DerefMut)]
+struct GizmoBuffer<L: PxLayer>(Vec<(L, u8, Vec<(IVec2, IVec2)>)>);
+
+impl<L: PxLayer> Default for GizmoBuffer<L> {
+    fn default() -> Self {
+        Self(default())
+    }
+}
+
+/// System parameter for drawing lines, rectangles, and points for a single frame,
+/// like Bevy's `Gizmos`. Positions are in world pixels, like entities on [`PxCanvas::World`].
+/// Colors are palette indices. Drawn on the given layer, after its sprites, text, and lines.
+/// Requires the `line` feature.
+#[derive(SystemParam)]
+pub struct PxGizmos<'w, L: PxLayer> {
+    buffer: ResMut<'w, GizmoBuffer<L>>,
+}
+
+impl<L: PxLayer> PxGizmos<'_, L> {
+    /// Draws a line between two points
+    pub fn line(&mut self, layer: L, start: IVec2, end: IVec2, color: u8) {
+        self.buffer.push((layer, color, vec![(start, end)]));
+    }
+
+    /// Draws lines between consecutive points
+    pub fn linestrip(&mut self, layer: L, points: impl IntoIterator<Item = IVec2>, color: u8) {
+        let points = points.into_iter().collect::<Vec<_>>();
+        let segments = match &*points {
+            [point] => vec![(*point, *point)],
+            points => points
+                .windows(2)
+                .map(|segment| (segment[0], segment[1]))
+                .collect(),
+        };
+
+        self.buffer.push((layer, color, segments));
+    }
+
+    /// Draws the outline of a rectangle. Like [`PxRect`], `max` is exclusive.
+    pub fn rect(&mut self, layer: L, rect: IRect, color: u8) {
+        if rect.is_empty() {
+            return;
+        }
+
+        let max = rect.max - 1;
+        self.linestrip(
+            layer,
+            [
+                rect.min,
+                IVec2::new(max.x, rect.min.y),
+                max,
+                IVec2::new(rect.min.x, max.y),
+                rect.min,
+            ],
+            color,
+        );
+    }
+
+    /// Draws a single pixel
+    pub fn point(&mut self, layer: L, position: IVec2, color: u8) {
+        self.line(layer, position, position, color);
+    }
+}
+
+fn clear_gizmos<L: PxLayer>(mut buffer: ResMut<GizmoBuffer<L>>) {
+    buffer.clear();
+}
+
+fn extract_gizmos<L: PxLayer>(
+    buffer: Extract<Res<GizmoBuffer<L>>>,
+    mut drawables: ResMut<ExtractedDrawables<L>>,
+) {
+    drawables.0.insert(
+        TypeId::of::<GizmoBuffer<L>>(),
+        buffer
+            .iter()
+            .map(|(layer, color, segments)| {
+                let mut poses = HashSet::default();
+                stroke(segments.iter().copied(), 1, &mut poses);
+                let color = *color;
+
+                (
+                    layer.clone(),
+                    Box::new(move |image: &mut PxImage<Option<u8>>, camera: PxCamera| {
+                        let height = image.height() as i32;
+
+                        for &pos in &poses {
+                            let pos = camera.world_to_screen(pos, PxCanvas::World);
+                            image.set_pixel(IVec2::new(pos.x, height - 1 - pos.y), Some(color));
+                        }
+                    }) as DrawFn,
+                )
+            })
+            .collect(),
+    );
+}
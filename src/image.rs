@@ -1,36 +1,58 @@
+//! Images of palette indices, which can be used as canvases
+
 use anyhow::{anyhow, Result};
-use bevy::render::render_resource::TextureFormat;
+use bevy::{
+    image::{CompressedImageFormats, ImageSampler, ImageType},
+    render::{render_asset::RenderAssetUsages, render_resource::TextureFormat},
+    tasks::{ComputeTaskPool, ParallelSliceMut, TaskPool},
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{math::RectExt, palette::Palette, pixel::Pixel, prelude::*};
 
+// Slices with more rows than this are iterated over in parallel
+const PARALLEL_ROWS: usize = 64;
+
+/// An image of [`Pixel`]s, stored in rows from top to bottom. `PxImage<u8>` is an opaque canvas,
+/// and `PxImage<Option<u8>>` is a canvas that can have transparent pixels.
 #[derive(Serialize, Deserialize, Clone, Reflect, Debug)]
-pub(crate) struct PxImage<P: Pixel> {
+pub struct PxImage<P: Pixel> {
     image: Vec<P>,
     width: usize,
 }
 
 impl<P: Pixel> PxImage<P> {
-    pub(crate) fn new(image: Vec<P>, width: usize) -> Self {
+    /// Creates an image from its pixels, in rows from top to bottom
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is 0 or the number of pixels isn't a multiple of `width`
+    pub fn new(image: Vec<P>, width: usize) -> Self {
+        assert!(width > 0, "image width is 0");
+        assert!(
+            image.len().is_multiple_of(width),
+            "image has {} pixels, which isn't a multiple of its width of {width}",
+            image.len()
+        );
+
         Self { image, width }
     }
 
-    pub(crate) fn empty(size: UVec2) -> Self {
+    /// Creates an image of the given size, filled with the default pixel
+    pub fn empty(size: UVec2) -> Self {
         Self {
             image: vec![default(); (size.x * size.y) as usize],
             width: size.x as usize,
         }
     }
 
-    pub(crate) fn empty_from_image(image: &Image) -> Self {
-        Self::empty(image.size())
-    }
-
-    pub(crate) fn pixel(&self, position: IVec2) -> P {
+    /// Gets the pixel at the given position, with y down. Panics if it's out of bounds.
+    pub fn pixel(&self, position: IVec2) -> P {
         self.image[(position.x + position.y * self.width as i32) as usize]
     }
 
-    pub(crate) fn get_pixel(&self, position: IVec2) -> Option<P> {
+    /// Gets the pixel at the given position, with y down, or `None` if it's out of bounds
+    pub fn get_pixel(&self, position: IVec2) -> Option<P> {
         IRect {
             min: IVec2::splat(0),
             max: IVec2::new(self.width as i32, (self.image.len() / self.width) as i32),
@@ -39,16 +61,25 @@ impl<P: Pixel> PxImage<P> {
         .then(|| self.pixel(position))
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn size(&self) -> UVec2 {
+    /// Sets the pixel at the given position, with y down. Does nothing if it's out of bounds.
+    pub fn set_pixel(&mut self, position: IVec2, pixel: P) {
+        if self.get_pixel(position).is_some() {
+            self.image[(position.x + position.y * self.width as i32) as usize] = pixel;
+        }
+    }
+
+    /// The size of the image
+    pub fn size(&self) -> UVec2 {
         UVec2::new(self.width as u32, (self.image.len() / self.width) as u32)
     }
 
-    pub(crate) fn width(&self) -> usize {
+    /// The width of the image
+    pub fn width(&self) -> usize {
         self.width
     }
 
-    pub(crate) fn height(&self) -> usize {
+    /// The height of the image
+    pub fn height(&self) -> usize {
         self.image.len() / self.width
     }
 
@@ -56,6 +87,11 @@ impl<P: Pixel> PxImage<P> {
         self.image.len()
     }
 
+    /// Iterates over the pixels, in rows from top to bottom
+    pub fn iter(&self) -> impl Iterator<Item = &P> {
+        self.image.iter()
+    }
+
     #[allow(unused)]
     pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut P> {
         self.image.iter_mut()
@@ -125,13 +161,29 @@ impl<P: Pixel> PxImage<P> {
         }
     }
 
-    pub(crate) fn clear(&mut self) {
+    /// Sets every pixel to the default pixel
+    pub fn clear(&mut self) {
         self.image.fill(default());
     }
+
+    // Resizes the image and sets every pixel to the default pixel, reusing the allocation
+    pub(crate) fn reset(&mut self, size: UVec2) {
+        self.image.clear();
+        self.image.resize((size.x * size.y) as usize, default());
+        self.width = size.x as usize;
+    }
 }
 
 impl PxImage<Option<u8>> {
-    pub(crate) fn palette_indices(palette: &Palette, image: &Image) -> Result<Self> {
+    /// Pixels with 0 alpha, or with the color `transparent_color`, are transparent
+    pub(crate) fn palette_indices(
+        palette: &Palette,
+        image: &Image,
+        transparent_color: Option<[u8; 3]>,
+    ) -> Result<Self> {
+        // Neighboring pixels are usually the same color, so remember the last lookup
+        let mut last = None;
+
         Ok(Self {
             image: image
                 .convert(TextureFormat::Rgba8UnormSrgb)
@@ -139,23 +191,34 @@ impl PxImage<Option<u8>> {
                 .data
                 .chunks_exact(4)
                 .map(|color| {
-                    (color[3] != 0)
-                        .then(|| {
-                            palette
-                                .indices
-                                .get(&[color[0], color[1], color[2]])
-                                .copied()
-                                .ok_or_else(|| {
-                                    anyhow!(
-                                        "a sprite contained a color `#{:02X}{:02X}{:02X}` \
-                                        that wasn't in the palette",
-                                        color[0],
-                                        color[1],
-                                        color[2]
-                                    )
-                                })
-                        })
-                        .transpose()
+                    if color[3] == 0 {
+                        return Ok(None);
+                    }
+
+                    let color = [color[0], color[1], color[2]];
+
+                    if Some(color) == transparent_color {
+                        return Ok(None);
+                    }
+
+                    if let Some((last_color, index)) = last {
+                        if last_color == color {
+                            return Ok(Some(index));
+                        }
+                    }
+
+                    let index = palette.indices.get(&color).copied().ok_or_else(|| {
+                        anyhow!(
+                            "a sprite contained a color `#{:02X}{:02X}{:02X}` \
+                            that wasn't in the palette",
+                            color[0],
+                            color[1],
+                            color[2]
+                        )
+                    })?;
+
+                    last = Some((color, index));
+                    Ok(Some(index))
                 })
                 .collect::<Result<_>>()?,
             width: image.texture_descriptor.size.width as usize,
@@ -163,7 +226,9 @@ impl PxImage<Option<u8>> {
     }
 
     pub(crate) fn trim_right(&mut self) {
-        while (0..self.height()).all(|row| self.image[self.width * (row + 1) - 1].is_none()) {
+        while self.width > 0
+            && (0..self.height()).all(|row| self.image[self.width * (row + 1) - 1].is_none())
+        {
             for row in (0..self.height()).rev() {
                 self.image.remove(row * self.width + self.width - 1);
             }
@@ -181,27 +246,47 @@ pub(crate) struct PxImageSliceMut<'a, P: Pixel> {
 
 impl<P: Pixel> PxImageSliceMut<'_, P> {
     /// First `usize` is the index in the slice. Second `usize` is the index in the image.
-    pub(crate) fn for_each_mut(&mut self, f: impl Fn(usize, usize, &mut P)) {
+    /// Large slices are iterated over in parallel, in chunks of rows.
+    pub(crate) fn for_each_mut(&mut self, f: impl Fn(usize, usize, &mut P) + Sync) {
         let row_min = self.slice.min.x.clamp(0, self.width as i32) as usize;
         let row_max = self.slice.max.x.clamp(0, self.width as i32) as usize;
         let max_y = self.image.len() as i32;
+        let min_y = self.slice.min.y.clamp(0, max_y) as usize;
+        let slice = self.slice;
+        let width = self.width;
+
+        let for_each_in_row = |i: usize, row: &mut [P]| {
+            for (j, pixel) in row[row_min..row_max].iter_mut().enumerate() {
+                let j = row_min + j;
+
+                f(
+                    ((i as i32 - slice.min.y) * (slice.max.x - slice.min.x)
+                        + (j as i32 - slice.min.x)) as usize,
+                    i * width + j,
+                    pixel,
+                );
+            }
+        };
+
+        let mut rows = &mut self.image[min_y..self.slice.max.y.clamp(0, max_y) as usize];
+
+        if rows.len() <= PARALLEL_ROWS {
+            for (i, row) in rows.iter_mut().enumerate() {
+                for_each_in_row(min_y + i, row);
+            }
+
+            return;
+        }
 
-        self.image.iter_mut().enumerate().collect::<Vec<_>>()
-            [self.slice.min.y.clamp(0, max_y) as usize..self.slice.max.y.clamp(0, max_y) as usize]
-            .iter_mut()
-            .for_each(|(i, row)| {
-                row.iter_mut().enumerate().collect::<Vec<_>>()[row_min..row_max]
-                    .iter_mut()
-                    .for_each(|(j, pixel)| {
-                        f(
-                            ((*i as i32 - self.slice.min.y) * (self.slice.max.x - self.slice.min.x)
-                                + (*j as i32 - self.slice.min.x))
-                                as usize,
-                            *i * self.width + *j,
-                            pixel,
-                        );
-                    });
-            });
+        rows.par_chunk_map_mut(
+            ComputeTaskPool::get_or_init(TaskPool::default),
+            PARALLEL_ROWS,
+            |chunk, rows| {
+                for (i, row) in rows.iter_mut().enumerate() {
+                    for_each_in_row(min_y + chunk * PARALLEL_ROWS + i, row);
+                }
+            },
+        );
     }
 
     pub(crate) fn pixel_mut(&mut self, position: IVec2) -> &mut P {
@@ -236,6 +321,20 @@ impl<P: Pixel> PxImageSliceMut<'_, P> {
             pixel.set(image.image[i]);
         });
     }
+
+    /// Draws the part of `image` that's under this slice. `image` must be the same size
+    /// as the sliced image.
+    pub(crate) fn draw_aligned(&mut self, image: &PxImage<impl Pixel>) {
+        self.for_each_mut(|_, i, pixel| {
+            pixel.set(image.image[i]);
+        });
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.for_each_mut(|_, _, pixel| {
+            *pixel = default();
+        });
+    }
 }
 
 impl<'a> PxImageSliceMut<'a, u8> {
@@ -256,3 +355,38 @@ impl<'a> PxImageSliceMut<'a, u8> {
         }
     }
 }
+
+// Decodes a PNG the same way the asset loaders do with default settings
+pub(crate) fn decode_png(bytes: &[u8]) -> Result<Image> {
+    Ok(Image::from_buffer(
+        bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::default(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_accepts_whole_rows() {
+        let image = PxImage::new(vec![0u8; 6], 3);
+        assert_eq!(image.size(), UVec2::new(3, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "image width is 0")]
+    fn new_rejects_zero_width() {
+        PxImage::new(Vec::<u8>::new(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't a multiple of its width")]
+    fn new_rejects_partial_rows() {
+        PxImage::new(vec![0u8; 5], 3);
+    }
+}
@@ -12,8 +12,12 @@ pub mod animation;
 mod button;
 mod camera;
 pub mod cursor;
+pub mod dither;
+pub mod drawable;
 pub mod filter;
-mod image;
+#[cfg(feature = "line")]
+mod gizmos;
+pub mod image;
 #[cfg(feature = "line")]
 mod line;
 mod map;
@@ -21,12 +25,14 @@ pub mod math;
 pub mod palette;
 #[cfg(feature = "particle")]
 mod particle;
-mod pixel;
+pub mod pixel;
 pub mod position;
 pub mod prelude;
 pub mod screen;
+mod screenshot;
 pub mod set;
 pub mod sprite;
+pub mod stats;
 mod text;
 mod ui;
 
@@ -41,6 +47,7 @@ use prelude::*;
 pub struct PxPlugin<L: PxLayer> {
     screen_size: ScreenSize,
     palette_path: PathBuf,
+    fixed_timestep_movement: bool,
     _l: PhantomData<L>,
 }
 
@@ -52,27 +59,44 @@ impl<L: PxLayer> PxPlugin<L> {
         Self {
             screen_size: screen_size.into(),
             palette_path: palette_path.into(),
+            fixed_timestep_movement: false,
             _l: PhantomData,
         }
     }
+
+    /// Integrate [`PxAcceleration`] and [`PxVelocity`] in [`FixedUpdate`] instead of
+    /// [`PreUpdate`], for deterministic movement. [`PxPosition`] is still updated to match
+    /// [`PxSubPosition`] in [`PreUpdate`].
+    pub fn with_fixed_timestep_movement(mut self) -> Self {
+        self.fixed_timestep_movement = true;
+        self
+    }
 }
 
 impl<L: PxLayer> Plugin for PxPlugin<L> {
     fn build(&self, app: &mut App) {
+        // Split in two, since there are more plugins than a tuple of plugins can hold
         app.add_plugins((
             animation::plug,
             button::plug,
             camera::plug,
             cursor::plug,
+            drawable::plug::<L>,
             filter::plug::<L>,
             #[cfg(feature = "line")]
-            line::plug::<L>,
+            (gizmos::plug::<L>, line::plug::<L>),
             map::plug::<L>,
+        ))
+        .add_plugins((
             palette::plug(self.palette_path.clone()),
-            position::plug::<L>,
+            position::plug::<L>(self.fixed_timestep_movement),
             screen::Plug::<L>::new(self.screen_size),
+            screenshot::plug,
+            set::plug,
             sprite::plug::<L>,
+            stats::plug::<L>,
             text::plug::<L>,
+            ui::plug,
             #[cfg(feature = "particle")]
             (RngPlugin::default(), particle::plug::<L>),
         ));
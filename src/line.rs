@@ -1,6 +1,9 @@
-use std::time::Duration;
+use std::{borrow::Cow, f32::consts::FRAC_PI_4, time::Duration};
 
-use bevy::render::{sync_world::RenderEntity, Extract, RenderApp};
+use bevy::{
+    render::{sync_world::RenderEntity, Extract, RenderApp},
+    utils::HashSet,
+};
 use line_drawing::Bresenham;
 
 use crate::{
@@ -22,6 +25,22 @@ pub(crate) fn plug<L: PxLayer>(app: &mut App) {
 #[require(DefaultPxFilterLayers, PxCanvas)]
 pub struct PxLine(pub Vec<IVec2>);
 
+/// Width of a [`PxLine`], in pixels. Defaults to 1.
+#[derive(Component, Deref, DerefMut, Clone, Copy, Debug)]
+pub struct PxLineWidth(pub u32);
+
+impl Default for PxLineWidth {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl From<u32> for PxLineWidth {
+    fn from(width: u32) -> Self {
+        Self(width)
+    }
+}
+
 impl Spatial for PxLine {
     fn frame_size(&self) -> UVec2 {
         if self.len() == 0 {
@@ -39,11 +58,216 @@ impl Spatial for PxLine {
     }
 }
 
-impl Animation for (&PxLine, &PxFilterAsset) {
+/// A filled polygon. Pixels are filled by the even-odd rule, so self-intersecting polygons
+/// have holes. Drawn with the entity's filter, like a [`PxLine`]. Add [`PxLineWidth`]
+/// to also draw its edges.
+#[derive(Component, Deref, DerefMut, Clone, Default, Debug)]
+#[require(DefaultPxFilterLayers, PxCanvas)]
+pub struct PxPolygon(pub Vec<IVec2>);
+
+impl<T: IntoIterator<Item = IVec2>> From<T> for PxPolygon {
+    fn from(polygon: T) -> Self {
+        Self(polygon.into_iter().collect())
+    }
+}
+
+/// Draws a [`PxLine`] as dashes. Animate `offset` to march the dashes along the line.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PxLineDash {
+    /// Length of each dash, in pixels
+    pub on: u32,
+    /// Length of each gap, in pixels
+    pub off: u32,
+    /// How far along the line the pattern is shifted, in pixels
+    pub offset: f32,
+}
+
+impl PxLineDash {
+    fn on_at(self, length: u32) -> bool {
+        let period = (self.on + self.off) as f32;
+        if period == 0. {
+            return true;
+        }
+
+        (length as f32 - self.offset).rem_euclid(period) < self.on as f32
+    }
+}
+
+/// Draws arrowheads on the ends of a [`PxLine`]
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PxLineArrow {
+    /// Length of each side of the arrowhead, in pixels
+    pub size: u32,
+    /// Whether to draw an arrowhead at the last point
+    pub at_end: bool,
+    /// Whether to draw an arrowhead at the first point
+    pub at_start: bool,
+}
+
+impl PxLineArrow {
+    /// Strokes an arrowhead at the first of `points`, pointing away from the next distinct point
+    fn stroke<'a>(
+        self,
+        mut points: impl Iterator<Item = &'a IVec2>,
+        width: u32,
+        poses: &mut HashSet<IVec2>,
+    ) {
+        let Some(&tip) = points.next() else {
+            return;
+        };
+
+        let Some(&from) = points.find(|&&point| point != tip) else {
+            return;
+        };
+
+        let back = (from - tip).as_vec2().normalize() * self.size as f32;
+
+        stroke(
+            [FRAC_PI_4, -FRAC_PI_4].map(|angle| {
+                (
+                    tip,
+                    tip + Vec2::from_angle(angle).rotate(back).round().as_ivec2(),
+                )
+            }),
+            width,
+            poses,
+        );
+    }
+}
+
+/// Outline of a circle, drawn with the entity's filter, like a [`PxLine`].
+/// Uses the same coordinates as [`PxLine`]'s points. Add [`PxLineWidth`] for a thicker outline.
+#[derive(Component, Clone, Copy, Default, Debug)]
+#[require(DefaultPxFilterLayers, PxCanvas)]
+pub struct PxCircle {
+    /// The center
+    pub center: IVec2,
+    /// The radius, in pixels
+    pub radius: u32,
+}
+
+/// Add to an entity with a [`PxRect`] and a [`PxFilter`] to draw the rectangle's outline
+/// with the filter, like a [`PxLine`]. Add [`PxLineWidth`] for a thicker outline.
+#[derive(Component, Clone, Copy, Default, Debug)]
+#[require(DefaultPxFilterLayers, PxCanvas)]
+pub struct PxStroke;
+
+pub(crate) type LineShapes<'a> = (
+    Option<&'a PxLine>,
+    Option<&'a PxSubLine>,
+    Option<&'a PxPolygon>,
+    Option<&'a PxCircle>,
+    Option<(&'a PxRect, &'a PxStroke)>,
+);
+
+fn mark(pos: IVec2, width: u32, poses: &mut HashSet<IVec2>) {
+    let width = width as i32;
+
+    for x in -(width - 1) / 2..=width / 2 {
+        for y in -(width - 1) / 2..=width / 2 {
+            poses.insert(pos + IVec2::new(x, y));
+        }
+    }
+}
+
+pub(crate) fn stroke(
+    points: impl IntoIterator<Item = (IVec2, IVec2)>,
+    width: u32,
+    poses: &mut HashSet<IVec2>,
+) {
+    for (start, end) in points {
+        for pos in Bresenham::new(start.into(), end.into()) {
+            mark(pos.into(), width, poses);
+        }
+    }
+}
+
+// Midpoint circle algorithm
+fn stroke_circle(center: IVec2, radius: u32, width: u32, poses: &mut HashSet<IVec2>) {
+    let mut x = radius as i32;
+    let mut y = 0;
+    let mut error = 1 - x;
+
+    while x >= y {
+        for offset in [
+            IVec2::new(x, y),
+            IVec2::new(y, x),
+            IVec2::new(-y, x),
+            IVec2::new(-x, y),
+            IVec2::new(-x, -y),
+            IVec2::new(-y, -x),
+            IVec2::new(y, -x),
+            IVec2::new(x, -y),
+        ] {
+            mark(center + offset, width, poses);
+        }
+
+        y += 1;
+
+        if error < 0 {
+            error += 2 * y + 1;
+        } else {
+            x -= 1;
+            error += 2 * (y - x) + 1;
+        }
+    }
+}
+
+fn fill(polygon: &[IVec2], size: UVec2, poses: &mut HashSet<IVec2>) {
+    let Some((min_y, max_y)) =
+        polygon
+            .iter()
+            .map(|point| point.y)
+            .fold(None, |bounds, y| match bounds {
+                Some((min, max)) => Some((y.min(min), y.max(max))),
+                None => Some((y, y)),
+            })
+    else {
+        return;
+    };
+
+    let edges = polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .filter(|(start, end)| start.y != end.y)
+        .collect::<Vec<_>>();
+    let mut crossings = Vec::new();
+
+    for y in min_y.max(0)..=max_y.min(size.y as i32 - 1) {
+        crossings.clear();
+
+        // Half-open, so vertices aren't counted twice
+        for (start, end) in &edges {
+            if (y >= start.y) != (y >= end.y) {
+                crossings.push(
+                    start.x as f32
+                        + (y - start.y) as f32 * (end.x - start.x) as f32
+                            / (end.y - start.y) as f32,
+                );
+            }
+        }
+
+        crossings.sort_by(f32::total_cmp);
+
+        for span in crossings.chunks_exact(2) {
+            for x in (span[0].ceil() as i32).max(0)..(span[1].ceil() as i32).min(size.x as i32) {
+                poses.insert(IVec2::new(x, y));
+            }
+        }
+    }
+}
+
+impl Animation
+    for (
+        LineShapes<'_>,
+        &PxFilterAsset,
+        (Option<PxLineWidth>, Option<PxLineDash>, Option<PxLineArrow>),
+    )
+{
     type Param = IVec2;
 
     fn frame_count(&self) -> usize {
-        let (_, PxFilterAsset(filter)) = self;
+        let (_, PxFilterAsset(filter), _) = self;
         filter.area() / filter.width()
     }
 
@@ -51,22 +275,107 @@ impl Animation for (&PxLine, &PxFilterAsset) {
         &self,
         param: Self::Param,
         image: &mut PxImageSliceMut<impl Pixel>,
-        frame: impl Fn(UVec2) -> usize,
-        _: impl Fn(u8) -> u8,
+        frame: impl Fn(UVec2) -> usize + Sync,
+        _: impl Fn(u8) -> u8 + Sync,
     ) {
-        let (line, PxFilterAsset(filter)) = self;
-        for (start, end) in line.iter().zip(line.iter().skip(1)) {
-            let start = *start + param;
-            let end = *end + param;
-
-            for (x, y) in Bresenham::new(start.into(), end.into()) {
-                if let Some(pixel) = image.get_pixel_mut((x, y).into()) {
-                    if let Some(pixel) = pixel.get_value_mut() {
-                        *pixel = filter.pixel(IVec2::new(
-                            *pixel as i32,
-                            frame(UVec2::new(x as u32, y as u32)) as i32,
-                        ));
+        let ((line, sub_line, polygon, circle, rect), PxFilterAsset(filter), (width, dash, arrow)) =
+            *self;
+        let stroke_width = width.unwrap_or_default().0;
+        // Collect the points first so that overlapping points are only filtered once
+        let mut poses = HashSet::default();
+
+        let line = line.map(|line| Cow::Borrowed(&***line)).or_else(|| {
+            sub_line.map(|sub_line| {
+                Cow::Owned(
+                    sub_line
+                        .iter()
+                        .map(|point| point.round().as_ivec2())
+                        .collect(),
+                )
+            })
+        });
+
+        if let Some(line) = line {
+            let mut length = 0;
+
+            for (start, end) in line.iter().zip(line.iter().skip(1)) {
+                let mut points = Bresenham::new((*start + param).into(), (*end + param).into());
+
+                // Each segment starts where the last one ended
+                if length > 0 {
+                    points.next();
+                }
+
+                for pos in points {
+                    if dash.is_none_or(|dash| dash.on_at(length)) {
+                        mark(pos.into(), stroke_width, &mut poses);
                     }
+
+                    length += 1;
+                }
+            }
+
+            if let Some(arrow) = arrow {
+                let line = line.iter().map(|&point| point + param).collect::<Vec<_>>();
+
+                if arrow.at_end {
+                    arrow.stroke(line.iter().rev(), stroke_width, &mut poses);
+                }
+
+                if arrow.at_start {
+                    arrow.stroke(line.iter(), stroke_width, &mut poses);
+                }
+            }
+        }
+
+        if let Some(&PxCircle { center, radius }) = circle {
+            stroke_circle(center + param, radius, stroke_width, &mut poses);
+        }
+
+        if let Some((rect, _)) = rect {
+            let min = rect.min + param;
+            let max = rect.max + param - IVec2::ONE;
+
+            stroke(
+                [
+                    (min, IVec2::new(max.x, min.y)),
+                    (IVec2::new(max.x, min.y), max),
+                    (max, IVec2::new(min.x, max.y)),
+                    (IVec2::new(min.x, max.y), min),
+                ],
+                stroke_width,
+                &mut poses,
+            );
+        }
+
+        if let Some(polygon) = polygon {
+            let polygon = polygon
+                .iter()
+                .map(|&point| point + param)
+                .collect::<Vec<_>>();
+
+            fill(
+                &polygon,
+                UVec2::new(image.width(), image.height()),
+                &mut poses,
+            );
+
+            if let Some(PxLineWidth(width)) = width {
+                stroke(
+                    polygon
+                        .iter()
+                        .copied()
+                        .zip(polygon.iter().copied().cycle().skip(1)),
+                    width,
+                    &mut poses,
+                );
+            }
+        }
+
+        for pos in poses {
+            if let Some(pixel) = image.get_pixel_mut(pos) {
+                if let Some(pixel) = pixel.get_value_mut() {
+                    *pixel = filter.pixel(IVec2::new(*pixel as i32, frame(pos.as_uvec2()) as i32));
                 }
             }
         }
@@ -79,36 +388,197 @@ impl<T: IntoIterator<Item = IVec2>> From<T> for PxLine {
     }
 }
 
+/// Point list for a line with sub-pixel points, which are rounded when it's drawn. Use this
+/// instead of [`PxLine`] for smoothly moving lines. Drawn like a [`PxLine`], and supports
+/// the same components.
+#[derive(Component, Deref, DerefMut, Clone, Default, Debug)]
+#[require(DefaultPxFilterLayers, PxCanvas)]
+pub struct PxSubLine(pub Vec<Vec2>);
+
+impl<T: IntoIterator<Item = Vec2>> From<T> for PxSubLine {
+    fn from(line: T) -> Self {
+        Self(line.into_iter().collect())
+    }
+}
+
+impl PxLine {
+    /// Creates a line along the Bézier curve with the given control points, made of `segments`
+    /// straight segments
+    pub fn from_bezier(points: &[IVec2], segments: usize) -> Self {
+        if points.is_empty() {
+            return default();
+        }
+
+        let segments = segments.max(1);
+        let mut curve = Vec::with_capacity(points.len());
+
+        Self(
+            (0..=segments)
+                .map(|segment| {
+                    let t = segment as f32 / segments as f32;
+
+                    // De Casteljau's algorithm
+                    curve.clear();
+                    curve.extend(points.iter().map(|point| point.as_vec2()));
+
+                    for len in (1..points.len()).rev() {
+                        for i in 0..len {
+                            curve[i] = curve[i].lerp(curve[i + 1], t);
+                        }
+                    }
+
+                    curve[0].round().as_ivec2()
+                })
+                .collect(),
+        )
+    }
+
+    /// Creates a line along the Catmull-Rom spline through the given points, with `segments`
+    /// straight segments between each pair of points
+    pub fn from_catmull_rom(points: &[IVec2], segments: usize) -> Self {
+        let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+            return default();
+        };
+
+        let segments = segments.max(1);
+        let points = [first]
+            .into_iter()
+            .chain(points.iter().copied())
+            .chain([last])
+            .map(|point| point.as_vec2())
+            .collect::<Vec<_>>();
+
+        Self(
+            points
+                .windows(4)
+                .flat_map(|window| {
+                    let [p0, p1, p2, p3] = [window[0], window[1], window[2], window[3]];
+
+                    (0..segments).map(move |segment| {
+                        let t = segment as f32 / segments as f32;
+                        let t2 = t * t;
+                        let t3 = t2 * t;
+
+                        (0.5 * (2. * p1
+                            + (p2 - p0) * t
+                            + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t2
+                            + (3. * p1 - p0 - 3. * p2 + p3) * t3))
+                            .round()
+                            .as_ivec2()
+                    })
+                })
+                .chain([last])
+                .collect(),
+        )
+    }
+}
+
 pub(crate) type LineComponents<L> = (
-    &'static PxLine,
+    AnyOf<(
+        &'static PxLine,
+        &'static PxSubLine,
+        &'static PxPolygon,
+        &'static PxCircle,
+        (&'static PxRect, &'static PxStroke),
+    )>,
     &'static PxFilter,
     &'static PxFilterLayers<L>,
     &'static PxCanvas,
     Option<&'static PxAnimation>,
+    (
+        Option<&'static PxLineWidth>,
+        Option<&'static PxLineDash>,
+        Option<&'static PxLineArrow>,
+    ),
 );
 
 fn extract_lines<L: PxLayer>(
-    lines: Extract<Query<(LineComponents<L>, &InheritedVisibility, RenderEntity)>>,
+    lines: Extract<
+        Query<(
+            LineComponents<L>,
+            &InheritedVisibility,
+            Has<PxHidden>,
+            RenderEntity,
+        )>,
+    >,
     mut cmd: Commands,
 ) {
-    for ((line, filter, layers, &canvas, animation), visibility, id) in &lines {
-        if !visibility.get() {
+    for (
+        (line, filter, layers, &canvas, animation, (width, dash, arrow)),
+        visibility,
+        hidden,
+        id,
+    ) in &lines
+    {
+        if hidden || !visibility.get() {
+            cmd.entity(id).insert(PxHidden);
             continue;
         }
 
         let mut entity = cmd.entity(id);
-        entity.insert((line.clone(), filter.clone(), layers.clone(), canvas));
+        entity.insert((filter.clone(), layers.clone(), canvas));
+        entity.remove::<PxHidden>();
+
+        let (line, sub_line, polygon, circle, rect) = line;
+
+        if let Some(line) = line {
+            entity.insert(line.clone());
+        } else {
+            entity.remove::<PxLine>();
+        }
+
+        if let Some(sub_line) = sub_line {
+            entity.insert(sub_line.clone());
+        } else {
+            entity.remove::<PxSubLine>();
+        }
+
+        if let Some(polygon) = polygon {
+            entity.insert(polygon.clone());
+        } else {
+            entity.remove::<PxPolygon>();
+        }
+
+        if let Some(&circle) = circle {
+            entity.insert(circle);
+        } else {
+            entity.remove::<PxCircle>();
+        }
+
+        if let Some((&rect, &stroke)) = rect {
+            entity.insert((rect, stroke));
+        } else {
+            entity.remove::<PxStroke>();
+        }
 
         if let Some(animation) = animation {
             entity.insert(*animation);
         } else {
             entity.remove::<PxAnimation>();
         }
+
+        if let Some(&width) = width {
+            entity.insert(width);
+        } else {
+            entity.remove::<PxLineWidth>();
+        }
+
+        if let Some(&dash) = dash {
+            entity.insert(dash);
+        } else {
+            entity.remove::<PxLineDash>();
+        }
+
+        if let Some(&arrow) = arrow {
+            entity.insert(arrow);
+        } else {
+            entity.remove::<PxLineArrow>();
+        }
     }
 }
 
 pub(crate) fn draw_line(
-    line: &PxLine,
+    line: LineShapes,
     filter: &PxFilterAsset,
     image: &mut PxImageSliceMut<impl Pixel>,
     canvas: PxCanvas,
@@ -119,11 +589,20 @@ pub(crate) fn draw_line(
         PxAnimationFrameTransition,
         Duration,
     )>,
+    (width, dash, arrow): (
+        Option<&PxLineWidth>,
+        Option<&PxLineDash>,
+        Option<&PxLineArrow>,
+    ),
     camera: PxCamera,
 ) {
     // TODO Make an `animated_line` example
     draw_animation(
-        &(line, filter),
+        &(
+            line,
+            filter,
+            (width.copied(), dash.copied(), arrow.copied()),
+        ),
         match canvas {
             PxCanvas::World => -*camera,
             PxCanvas::Camera => IVec2::ZERO,
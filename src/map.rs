@@ -1,8 +1,8 @@
 use std::mem::replace;
 
-use anyhow::{Error, Result};
+use anyhow::{anyhow, Error, Result};
 use bevy::{
-    asset::{io::Reader, AssetLoader, LoadContext},
+    asset::{io::Reader, AssetLoader, AssetPath, LoadContext},
     image::{CompressedImageFormats, ImageLoader, ImageLoaderSettings},
     render::{
         render_asset::{PrepareAssetError, RenderAsset, RenderAssetPlugin},
@@ -14,9 +14,9 @@ use bevy::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    animation::{AnimatedAssetComponent, PxAnimation},
-    image::PxImage,
-    palette::asset_palette,
+    animation::{AnimatedAssetComponent, Animation, PxAnimation},
+    image::{decode_png, PxImage},
+    palette::{asset_palette, Palette},
     position::{DefaultLayer, PxLayer, Spatial},
     prelude::*,
     sprite::PxSpriteAsset,
@@ -29,9 +29,14 @@ pub(crate) fn plug<L: PxLayer>(app: &mut App) {
         SyncComponentPlugin::<PxTile>::default(),
     ))
     .init_asset::<PxTileset>()
-    .init_asset_loader::<PxTilesetLoader>()
-    .sub_app_mut(RenderApp)
-    .add_systems(ExtractSchedule, (extract_maps::<L>, extract_tiles));
+    .init_asset_loader::<PxTilesetLoader>();
+
+    #[cfg(feature = "tiled")]
+    app.init_asset::<PxTiledMap>()
+        .init_asset_loader::<PxTiledMapLoader>();
+
+    app.sub_app_mut(RenderApp)
+        .add_systems(ExtractSchedule, (extract_maps::<L>, extract_tiles));
 }
 
 #[derive(Serialize, Deserialize)]
@@ -66,73 +71,88 @@ impl AssetLoader for PxTilesetLoader {
         let image = ImageLoader::new(CompressedImageFormats::NONE)
             .load(reader, &settings.image_loader_settings, load_context)
             .await?;
-        let palette = asset_palette().await;
-        let indices = PxImage::palette_indices(palette, &image)?;
-        let tile_size = settings.tile_size;
-        let tile_area = tile_size.x * tile_size.y;
-        let mut tileset = Vec::default();
-        let mut tile = Vec::with_capacity(tile_area as usize);
-        let tileset_width = image.texture_descriptor.size.width;
-        let tile_tileset_width = tileset_width / tile_size.x;
-        let mut max_frame_count = 0;
-
-        for i in 0..indices.area() {
-            let tile_index = i as u32 / tile_area;
-            let tile_pos = i as u32 % tile_area;
-
-            tile.push(
-                indices.pixel(
-                    (UVec2::new(
-                        tile_index % tile_tileset_width,
-                        tile_index / tile_tileset_width,
-                    ) * tile_size
-                        + UVec2::new(tile_pos % tile_size.x, tile_pos / tile_size.y))
-                    .as_ivec2(),
-                ),
-            );
 
-            if tile_pos == tile_area - 1
-                && tile_index % tile_tileset_width == tile_tileset_width - 1
-            {
-                while tile.len() > tile_area as usize
-                    && tile[tile.len() - tile_area as usize..tile.len()]
-                        .iter()
-                        .all(|pixel| pixel.is_none())
-                {
-                    tile.truncate(tile.len() - tile_area as usize);
-                }
+        load_tileset(&image, asset_palette().await, settings.tile_size)
+    }
 
-                let frame_count = tile.len() / tile_area as usize;
-                if max_frame_count < frame_count {
-                    max_frame_count = frame_count;
-                }
+    fn extensions(&self) -> &[&str] {
+        &["px_tileset.png"]
+    }
+}
+
+fn load_tileset(image: &Image, palette: &Palette, tile_size: UVec2) -> Result<PxTileset> {
+    let size = image.size();
+    if tile_size.x == 0
+        || tile_size.y == 0
+        || size.x < tile_size.x
+        || !size.x.is_multiple_of(tile_size.x)
+        || !size.y.is_multiple_of(tile_size.y)
+    {
+        return Err(anyhow!(
+            "tileset's size of {size} isn't a multiple of its `tile_size` of {tile_size}"
+        ));
+    }
 
-                tileset.push(PxSpriteAsset {
-                    data: PxImage::new(
-                        replace(&mut tile, Vec::with_capacity(tile_area as usize)),
-                        tile_size.x as usize,
-                    ),
-                    frame_size: tile_area as usize,
-                });
+    let indices = PxImage::palette_indices(palette, image, None)?;
+    let tile_area = tile_size.x * tile_size.y;
+    let mut tileset = Vec::default();
+    let mut tile = Vec::with_capacity(tile_area as usize);
+    let tileset_width = image.texture_descriptor.size.width;
+    let tile_tileset_width = tileset_width / tile_size.x;
+    let mut max_frame_count = 0;
+
+    for i in 0..indices.area() {
+        let tile_index = i as u32 / tile_area;
+        let tile_pos = i as u32 % tile_area;
+
+        tile.push(
+            indices.pixel(
+                (UVec2::new(
+                    tile_index % tile_tileset_width,
+                    tile_index / tile_tileset_width,
+                ) * tile_size
+                    + UVec2::new(tile_pos % tile_size.x, tile_pos / tile_size.x))
+                .as_ivec2(),
+            ),
+        );
+
+        if tile_pos == tile_area - 1 && tile_index % tile_tileset_width == tile_tileset_width - 1 {
+            while tile.len() > tile_area as usize
+                && tile[tile.len() - tile_area as usize..tile.len()]
+                    .iter()
+                    .all(|pixel| pixel.is_none())
+            {
+                tile.truncate(tile.len() - tile_area as usize);
             }
-        }
 
-        Ok(PxTileset {
-            tileset,
-            tile_size,
-            max_frame_count,
-        })
-    }
+            let frame_count = tile.len() / tile_area as usize;
+            if max_frame_count < frame_count {
+                max_frame_count = frame_count;
+            }
 
-    fn extensions(&self) -> &[&str] {
-        &["px_tileset.png"]
+            tileset.push(PxSpriteAsset {
+                data: PxImage::new(
+                    replace(&mut tile, Vec::with_capacity(tile_area as usize)),
+                    tile_size.x as usize,
+                ),
+                frame_size: tile_area as usize,
+                trim: None,
+            });
+        }
     }
+
+    Ok(PxTileset {
+        tileset,
+        tile_size,
+        max_frame_count,
+    })
 }
 
 /// A tileset for a tilemap. Create a [`Handle<PxTileset>`] with a [`PxAssets<PxTileset>`]
 /// and an image file. The image file contains a column of tiles, ordered from bottom to top.
 /// For animated tilesets, add additional frames to the right of tiles, marking the end
-/// of an animation with a fully transparent tile or the end of the image.
+/// of an animation with a fully transparent tile or the end of the image. Each tile keeps
+/// its own frame count, so tiles of different lengths can share a tileset.
 /// See `assets/tileset/tileset.png` for an example.
 #[derive(Asset, Clone, Reflect, Debug)]
 pub struct PxTileset {
@@ -154,10 +174,215 @@ impl RenderAsset for PxTileset {
 }
 
 impl PxTileset {
+    /// Creates a tileset from the bytes of a PNG, such as one embedded with [`include_bytes!`],
+    /// without the asset server
+    pub fn from_png_bytes(bytes: &[u8], palette: &Palette, tile_size: UVec2) -> Result<Self> {
+        load_tileset(&decode_png(bytes)?, palette, tile_size)
+    }
+
     /// The size of tiles in the tileset
     pub fn tile_size(&self) -> UVec2 {
         self.tile_size
     }
+
+    /// The number of animation frames of the tile with the given texture index, or `None`
+    /// if the index is out of bounds. An animated map animates each tile over its own
+    /// frame count.
+    pub fn frame_count(&self, texture: u32) -> Option<usize> {
+        Some(self.tileset.get(texture as usize)?.frame_count())
+    }
+}
+
+#[cfg(feature = "tiled")]
+#[derive(Serialize, Deserialize, Default)]
+struct PxTiledMapLoaderSettings {
+    layer: Option<String>,
+}
+
+#[cfg(feature = "tiled")]
+#[derive(Default)]
+struct PxTiledMapLoader;
+
+#[cfg(feature = "tiled")]
+impl AssetLoader for PxTiledMapLoader {
+    type Asset = PxTiledMap;
+    type Settings = PxTiledMapLoaderSettings;
+    type Error = Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &PxTiledMapLoaderSettings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<PxTiledMap> {
+        // Tiled stores flipping and rotation in the high bits of each GID
+        const FLAGS: u32 = 0xF000_0000;
+
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let document = roxmltree::Document::parse(std::str::from_utf8(&bytes)?)?;
+        let map = document.root_element();
+
+        let tileset = map.children().find(|node| node.has_tag_name("tileset"));
+        let attribute_or = |node: Option<roxmltree::Node>, name, default| -> Result<u32> {
+            Ok(node
+                .and_then(|node| node.attribute(name))
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or(default))
+        };
+        let first_gid = attribute_or(tileset, "firstgid", 1)?;
+
+        // External tilesets keep everything but `firstgid` in a separate `.tsx` file
+        let external_tileset = match tileset.and_then(|tileset| tileset.attribute("source")) {
+            Some(source) => {
+                let path = load_context.asset_path().resolve_embed(source)?;
+                Some(String::from_utf8(
+                    load_context.read_asset_bytes(path).await?,
+                )?)
+            }
+            None => None,
+        };
+        let external_tileset = external_tileset
+            .as_deref()
+            .map(roxmltree::Document::parse)
+            .transpose()?;
+        let tileset = match &external_tileset {
+            Some(external_tileset) => Some(external_tileset.root_element()),
+            None => tileset,
+        };
+
+        // Animation frames are to the right of each tile, so each row is one tile
+        let columns = attribute_or(tileset, "columns", 1)?.max(1);
+
+        let layer = map
+            .children()
+            .filter(|node| node.has_tag_name("layer"))
+            .find(|layer| {
+                settings
+                    .layer
+                    .as_ref()
+                    .is_none_or(|name| layer.attribute("name") == Some(name))
+            })
+            .ok_or_else(|| match &settings.layer {
+                Some(name) => anyhow!("map has no tile layer named `{name}`"),
+                None => anyhow!("map has no tile layers"),
+            })?;
+
+        let attribute = |name| -> Result<u32> {
+            Ok(layer
+                .attribute(name)
+                .ok_or_else(|| anyhow!("tile layer is missing `{name}`"))?
+                .parse()?)
+        };
+        let size = UVec2::new(attribute("width")?, attribute("height")?);
+
+        let data = layer
+            .children()
+            .find(|node| node.has_tag_name("data"))
+            .ok_or_else(|| anyhow!("tile layer has no data"))?;
+
+        if data.attribute("encoding") != Some("csv") {
+            return Err(anyhow!(
+                "tile layer data must be CSV encoded. Set the map's tile layer format to CSV in \
+                Tiled's map properties."
+            ));
+        }
+
+        let gids = data
+            .text()
+            .unwrap_or_default()
+            .split(',')
+            .map(|gid| gid.trim().parse())
+            .collect::<Result<Vec<u32>, _>>()?;
+
+        if gids.len() != (size.x * size.y) as usize {
+            return Err(anyhow!(
+                "tile layer has {} tiles, but its size is {}x{}",
+                gids.len(),
+                size.x,
+                size.y
+            ));
+        }
+
+        let mut textures = vec![None; gids.len()];
+        for (i, gid) in gids.into_iter().enumerate() {
+            let gid = gid & !FLAGS;
+            if gid == 0 {
+                continue;
+            }
+
+            // Tiled's rows go from top to bottom
+            let i = i as u32;
+            let x = i % size.x;
+            let y = size.y - 1 - i / size.x;
+
+            textures[(x + y * size.x) as usize] = Some(
+                gid.checked_sub(first_gid)
+                    .ok_or_else(|| anyhow!("tile GID {gid} is not in the map's first tileset"))?
+                    / columns,
+            );
+        }
+
+        Ok(PxTiledMap { size, textures })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx"]
+    }
+}
+
+/// A tile layer loaded from a [Tiled](https://www.mapeditor.org/) map (`.tmx`). Spawn its tiles
+/// with [`PxTiledMap::spawn_tiles`]. The layer must be CSV encoded. Tiles are taken from the map's
+/// first tileset, which should use the same image as the [`PxTileset`] you render the map with.
+/// The tileset may be embedded in the map or an external `.tsx` file.
+/// Each row of that image is one [`PxTile::texture`], so any frame of an animated tile refers
+/// to that tile. Like any other asset, the tileset may only contain colors that are
+/// in the palette. To load a layer other than the first, set `layer` to its name
+/// in the `.tmx.meta` file's settings. Only Tiled maps are supported; LDtk projects are not.
+#[cfg(feature = "tiled")]
+#[derive(Asset, Clone, TypePath, Debug)]
+pub struct PxTiledMap {
+    size: UVec2,
+    textures: Vec<Option<u32>>,
+}
+
+#[cfg(feature = "tiled")]
+impl PxTiledMap {
+    /// The size of the layer in tiles
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// Gets the tile texture at the given position. Returns `None` if there is no tile there
+    /// or if the position is out of bounds.
+    pub fn texture(&self, at: UVec2) -> Option<u32> {
+        if at.x >= self.size.x {
+            return None;
+        }
+
+        self.textures
+            .get((at.x + at.y * self.size.x) as usize)
+            .copied()?
+    }
+
+    /// Spawns a [`PxTile`] for each tile in the layer, and returns the [`PxTiles`] to put
+    /// in a [`PxMap`]
+    pub fn spawn_tiles(&self, commands: &mut Commands) -> PxTiles {
+        let mut tiles = PxTiles::new(self.size);
+
+        for x in 0..self.size.x {
+            for y in 0..self.size.y {
+                let at = UVec2::new(x, y);
+
+                if let Some(texture) = self.texture(at) {
+                    tiles.set(Some(commands.spawn(PxTile::from(texture)).id()), at);
+                }
+            }
+        }
+
+        tiles
+    }
 }
 
 /// The tiles in a tilemap
@@ -243,17 +468,110 @@ impl AnimatedAssetComponent for PxMap {
     }
 }
 
+impl PxMap {
+    /// Checks whether the tile at the given position is [solid](PxTile::solid). Returns `false`
+    /// if there is no tile at the given position or if the position is out of bounds.
+    pub fn solid_at(&self, at: UVec2, tiles: &Query<&PxTile>) -> bool {
+        self.tiles
+            .get(at)
+            .and_then(|tile| tiles.get(tile).ok())
+            .is_some_and(|tile| tile.solid)
+    }
+
+    /// Converts the map to plain data, for saving. Returns `None` if the tileset
+    /// wasn't loaded from a path. See [`PxMapData`] for what is stored.
+    pub fn to_data(&self, tiles: &Query<&PxTile>, assets: &AssetServer) -> Option<PxMapData> {
+        Some(PxMapData {
+            tileset: assets.get_path(&self.tileset)?.into_owned(),
+            size: self.tiles.size(),
+            tiles: self
+                .tiles
+                .tiles
+                .iter()
+                .map(|&tile| tile.and_then(|tile| tiles.get(tile).ok()).cloned())
+                .collect(),
+        })
+    }
+
+    /// Creates a map from plain data, loading its tileset and spawning its tiles
+    pub fn from_data(data: &PxMapData, commands: &mut Commands, assets: &AssetServer) -> Self {
+        let mut tiles = PxTiles::new(data.size);
+
+        for (slot, tile) in tiles.tiles.iter_mut().zip(&data.tiles) {
+            *slot = tile.clone().map(|tile| commands.spawn(tile).id());
+        }
+
+        Self {
+            tiles,
+            tileset: assets.load(data.tileset.clone()),
+        }
+    }
+
+    /// Despawns the map's tiles, such as before replacing the map with [`PxMap::from_data`]
+    pub fn despawn_tiles(&self, commands: &mut Commands) {
+        for &tile in self.tiles.tiles.iter().flatten() {
+            commands.entity(tile).despawn();
+        }
+    }
+
+    /// Navability of each tile, for generating `seldom_map_nav` navmeshes
+    /// from the map's [solid](PxTile::solid) tiles. Pass it to `Navmeshes::generate`
+    /// with the map's size, [`PxTiles::size`], and the tile size, [`PxTileset::tile_size`],
+    /// so that navmesh positions are in pixels, relative to the map's bottom-left.
+    /// Requires the `nav` feature.
+    #[cfg(feature = "nav")]
+    pub fn navability<'a>(
+        &'a self,
+        tiles: &'a Query<&PxTile>,
+    ) -> impl Fn(UVec2) -> Navability + 'a {
+        |at| match self.solid_at(at, tiles) {
+            true => Navability::Solid,
+            false => Navability::Navable,
+        }
+    }
+}
+
+/// Plain data for a [`PxMap`], for saving and loading maps with [`PxMap::to_data`]
+/// and [`PxMap::from_data`]. Only the tileset and each tile's texture and solidity are stored.
+/// Other components, such as [`PxFilter`]s on tiles and [`PxAnimation`] on the map,
+/// are not stored, so add them again after loading.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PxMapData {
+    /// Path to the map's tileset
+    pub tileset: AssetPath<'static>,
+    /// Size of the map in tiles
+    pub size: UVec2,
+    /// The map's tiles, in rows from bottom to top. `None` where there is no tile.
+    pub tiles: Vec<Option<PxTile>>,
+}
+
 /// A tile. Must be added to tiles added to [`PxMap`].
-#[derive(Component, Clone, Default, Debug)]
+#[derive(Component, Serialize, Deserialize, Clone, Default, Debug)]
 #[require(Visibility)]
 pub struct PxTile {
     /// The index to the tile texture in the tileset
     pub texture: u32,
+    /// Whether the tile blocks movement. This does not affect rendering. Check it with
+    /// [`PxMap::solid_at`].
+    pub solid: bool,
 }
 
 impl From<u32> for PxTile {
     fn from(value: u32) -> Self {
-        Self { texture: value }
+        Self {
+            texture: value,
+            solid: false,
+        }
+    }
+}
+
+impl PxTile {
+    /// Creates a solid [`PxTile`] with the given texture
+    pub fn solid(texture: u32) -> Self {
+        Self {
+            texture,
+            solid: true,
+        }
     }
 }
 
@@ -264,15 +582,26 @@ pub(crate) type MapComponents<L> = (
     &'static PxCanvas,
     Option<&'static PxAnimation>,
     Option<&'static PxFilter>,
+    Option<&'static PxParallax>,
 );
 
 fn extract_maps<L: PxLayer>(
-    maps: Extract<Query<(MapComponents<L>, &InheritedVisibility, RenderEntity)>>,
+    maps: Extract<
+        Query<(
+            MapComponents<L>,
+            &InheritedVisibility,
+            Has<PxHidden>,
+            RenderEntity,
+        )>,
+    >,
     render_entities: Extract<Query<RenderEntity>>,
     mut cmd: Commands,
 ) {
-    for ((map, &position, layer, &canvas, animation, filter), visibility, id) in &maps {
-        if !visibility.get() {
+    for ((map, &position, layer, &canvas, animation, filter, parallax), visibility, hidden, id) in
+        &maps
+    {
+        if hidden || !visibility.get() {
+            cmd.entity(id).insert(PxHidden);
             continue;
         }
 
@@ -286,6 +615,7 @@ fn extract_maps<L: PxLayer>(
         }
 
         entity.insert((map, position, layer.clone(), canvas));
+        entity.remove::<PxHidden>();
 
         if let Some(animation) = animation {
             entity.insert(*animation);
@@ -298,22 +628,38 @@ fn extract_maps<L: PxLayer>(
         } else {
             entity.remove::<PxFilter>();
         }
+
+        if let Some(&parallax) = parallax {
+            entity.insert(parallax);
+        } else {
+            entity.remove::<PxParallax>();
+        }
     }
 }
 
 pub(crate) type TileComponents = (&'static PxTile, Option<&'static PxFilter>);
 
 fn extract_tiles(
-    tiles: Extract<Query<(TileComponents, &InheritedVisibility, RenderEntity)>>,
+    tiles: Extract<
+        Query<(
+            TileComponents,
+            &InheritedVisibility,
+            Has<PxHidden>,
+            RenderEntity,
+        )>,
+    >,
     mut cmd: Commands,
 ) {
-    for ((tile, filter), visibility, entity) in &tiles {
-        if !visibility.get() {
+    for ((tile, filter), visibility, hidden, entity) in &tiles {
+        // The map draws its tiles by entity, so hidden tiles must be marked, not just skipped
+        if hidden || !visibility.get() {
+            cmd.entity(entity).insert(PxHidden);
             continue;
         }
 
         let mut entity = cmd.entity(entity);
         entity.insert(tile.clone());
+        entity.remove::<PxHidden>();
 
         if let Some(filter) = filter {
             entity.insert(filter.clone());
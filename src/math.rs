@@ -12,6 +12,15 @@ pub trait RectExt {
 
     /// Subtracts an [`IVec2`] from the rectangle's points
     fn sub_ivec2(self, other: IVec2) -> Self;
+
+    /// Clamps the rectangle's points to be within `bounds`. The result may be empty.
+    fn clamp_to(self, bounds: Self) -> Self;
+
+    /// The overlap of two rectangles, or `None` if they don't overlap. Unlike
+    /// [`IRect::intersect`], rectangles that only share an edge don't overlap.
+    fn intersection(self, other: Self) -> Option<Self>
+    where
+        Self: Sized;
 }
 
 impl RectExt for IRect {
@@ -34,10 +43,22 @@ impl RectExt for IRect {
             max: self.max - other,
         }
     }
+
+    fn clamp_to(self, bounds: Self) -> Self {
+        Self {
+            min: self.min.clamp(bounds.min, bounds.max),
+            max: self.max.clamp(bounds.min, bounds.max),
+        }
+    }
+
+    fn intersection(self, other: Self) -> Option<Self> {
+        let intersection = self.intersect(other);
+        (!intersection.is_empty()).then_some(intersection)
+    }
 }
 
 /// An orthogonal direction
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Orthogonal {
     /// Right
     Right,
@@ -49,8 +70,70 @@ pub enum Orthogonal {
     Down,
 }
 
+impl Orthogonal {
+    /// A unit vector pointing in this direction
+    pub fn as_ivec2(self) -> IVec2 {
+        use Orthogonal::*;
+
+        match self {
+            Right => IVec2::X,
+            Up => IVec2::Y,
+            Left => IVec2::NEG_X,
+            Down => IVec2::NEG_Y,
+        }
+    }
+
+    /// The direction a vector points in, or `None` if it isn't a nonzero orthogonal vector
+    pub fn from_ivec2(vec: IVec2) -> Option<Self> {
+        use Orthogonal::*;
+
+        match (vec.x.signum(), vec.y.signum()) {
+            (1, 0) => Some(Right),
+            (0, 1) => Some(Up),
+            (-1, 0) => Some(Left),
+            (0, -1) => Some(Down),
+            _ => None,
+        }
+    }
+
+    /// This direction rotated a quarter turn clockwise
+    pub fn rotate_cw(self) -> Self {
+        use Orthogonal::*;
+
+        match self {
+            Right => Down,
+            Up => Right,
+            Left => Up,
+            Down => Left,
+        }
+    }
+
+    /// This direction rotated a quarter turn counterclockwise
+    pub fn rotate_ccw(self) -> Self {
+        self.opposite().rotate_cw()
+    }
+
+    /// The opposite direction
+    pub fn opposite(self) -> Self {
+        self.rotate_cw().rotate_cw()
+    }
+}
+
+impl From<Orthogonal> for Dir2 {
+    fn from(orthogonal: Orthogonal) -> Self {
+        use Orthogonal::*;
+
+        match orthogonal {
+            Right => Dir2::X,
+            Up => Dir2::Y,
+            Left => Dir2::NEG_X,
+            Down => Dir2::NEG_Y,
+        }
+    }
+}
+
 /// A diagonal direction
-#[derive(Copy, Clone)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Diagonal {
     /// Up-right
     UpRight,
@@ -74,4 +157,126 @@ impl Diagonal {
             DownRight => UVec2::new(1, 0),
         }
     }
+
+    /// A vector pointing in this direction, with 1 or -1 on each axis
+    pub fn as_ivec2(self) -> IVec2 {
+        self.as_uvec2().as_ivec2() * 2 - IVec2::ONE
+    }
+
+    /// The direction a vector points in, or `None` if it isn't a nonzero diagonal vector
+    pub fn from_ivec2(vec: IVec2) -> Option<Self> {
+        use Diagonal::*;
+
+        match (vec.x.signum(), vec.y.signum()) {
+            (1, 1) if vec.x == vec.y => Some(UpRight),
+            (-1, 1) if -vec.x == vec.y => Some(UpLeft),
+            (-1, -1) if vec.x == vec.y => Some(DownLeft),
+            (1, -1) if vec.x == -vec.y => Some(DownRight),
+            _ => None,
+        }
+    }
+
+    /// This direction rotated a quarter turn clockwise
+    pub fn rotate_cw(self) -> Self {
+        use Diagonal::*;
+
+        match self {
+            UpRight => DownRight,
+            UpLeft => UpRight,
+            DownLeft => UpLeft,
+            DownRight => DownLeft,
+        }
+    }
+
+    /// This direction rotated a quarter turn counterclockwise
+    pub fn rotate_ccw(self) -> Self {
+        self.opposite().rotate_cw()
+    }
+
+    /// The opposite direction
+    pub fn opposite(self) -> Self {
+        self.rotate_cw().rotate_cw()
+    }
+}
+
+impl From<Diagonal> for Dir2 {
+    fn from(diagonal: Diagonal) -> Self {
+        use Diagonal::*;
+
+        match diagonal {
+            UpRight => Dir2::NORTH_EAST,
+            UpLeft => Dir2::NORTH_WEST,
+            DownLeft => Dir2::SOUTH_WEST,
+            DownRight => Dir2::SOUTH_EAST,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(min: (i32, i32), max: (i32, i32)) -> IRect {
+        IRect::new(min.0, min.1, max.0, max.1)
+    }
+
+    #[test]
+    fn intersection_disjoint() {
+        assert_eq!(
+            rect((0, 0), (2, 2)).intersection(rect((5, 5), (7, 7))),
+            None
+        );
+        assert_eq!(
+            rect((0, 0), (2, 2)).intersection(rect((5, 0), (7, 2))),
+            None
+        );
+    }
+
+    #[test]
+    fn intersection_touching() {
+        // Sharing an edge
+        assert_eq!(
+            rect((0, 0), (2, 2)).intersection(rect((2, 0), (4, 2))),
+            None
+        );
+        assert_eq!(
+            rect((0, 0), (2, 2)).intersection(rect((0, 2), (2, 4))),
+            None
+        );
+        // Sharing a corner
+        assert_eq!(
+            rect((0, 0), (2, 2)).intersection(rect((2, 2), (4, 4))),
+            None
+        );
+    }
+
+    #[test]
+    fn intersection_overlapping() {
+        assert_eq!(
+            rect((0, 0), (3, 3)).intersection(rect((2, 1), (5, 5))),
+            Some(rect((2, 1), (3, 3))),
+        );
+    }
+
+    #[test]
+    fn intersection_contained() {
+        let outer = rect((0, 0), (10, 10));
+        let inner = rect((2, 3), (4, 5));
+        assert_eq!(outer.intersection(inner), Some(inner));
+        assert_eq!(inner.intersection(outer), Some(inner));
+    }
+
+    #[test]
+    fn clamp_to_outside() {
+        let bounds = rect((0, 0), (4, 4));
+        let clamped = rect((6, -3), (9, -1)).clamp_to(bounds);
+        assert_eq!(clamped, rect((4, 0), (4, 0)));
+        assert!(clamped.is_empty());
+    }
+
+    #[test]
+    fn clamp_to_inside() {
+        let bounds = rect((0, 0), (4, 4));
+        assert_eq!(rect((-2, 1), (2, 6)).clamp_to(bounds), rect((0, 1), (2, 4)));
+    }
 }
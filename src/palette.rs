@@ -1,11 +1,11 @@
 //! Color palettes
 
 use std::{
-    path::PathBuf,
-    sync::atomic::{AtomicBool, Ordering},
+    path::{Path, PathBuf},
+    sync::RwLock,
 };
 
-use anyhow::{Error, Result};
+use anyhow::{anyhow, Error, Result};
 use bevy::{
     asset::{io::Reader, AssetLoader, LoadContext},
     image::{CompressedImageFormats, ImageLoader, ImageLoaderSettings},
@@ -15,12 +15,14 @@ use bevy::{
 use event_listener::Event;
 use seldom_singleton::AssetSingleton;
 
-use crate::prelude::*;
+use crate::{position::PxLayer, prelude::*};
 
 pub(crate) fn plug(palette_path: PathBuf) -> impl Fn(&mut App) {
     move |app| {
         app.init_asset::<Palette>()
             .init_asset_loader::<PaletteLoader>()
+            .init_asset_loader::<GplPaletteLoader>()
+            .init_asset_loader::<HexPaletteLoader>()
             .add_systems(Startup, init_palette(palette_path.clone()))
             .add_systems(
                 PreUpdate,
@@ -43,11 +45,14 @@ impl AssetLoader for PaletteLoader {
         settings: &ImageLoaderSettings,
         load_context: &mut LoadContext<'_>,
     ) -> Result<Palette> {
-        Ok(Palette::new(
+        let palette = Palette::new(
             &ImageLoader::new(CompressedImageFormats::NONE)
                 .load(reader, settings, load_context)
                 .await?,
-        ))
+        );
+
+        palette.validate(load_context.path())?;
+        Ok(palette)
     }
 
     fn extensions(&self) -> &[&str] {
@@ -55,9 +60,121 @@ impl AssetLoader for PaletteLoader {
     }
 }
 
+/// Loads GIMP palettes (`.gpl`). The palette's `Columns` determines its layout for filters.
+#[derive(Default)]
+struct GplPaletteLoader;
+
+impl AssetLoader for GplPaletteLoader {
+    type Asset = Palette;
+    type Settings = ();
+    type Error = Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        &(): &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Palette> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let text = std::str::from_utf8(&bytes)?;
+
+        let mut lines = text.lines().map(str::trim);
+        if lines.next() != Some("GIMP Palette") {
+            return Err(anyhow!("GIMP palette is missing its `GIMP Palette` header"));
+        }
+
+        let mut columns = None;
+        let mut colors = Vec::new();
+
+        for line in lines {
+            if line.is_empty() || line.starts_with('#') || line.starts_with("Name:") {
+                continue;
+            }
+
+            if let Some(value) = line.strip_prefix("Columns:") {
+                columns = Some(value.trim().parse::<u32>()?).filter(|&columns| columns > 0);
+                continue;
+            }
+
+            let mut channels = line.split_whitespace();
+            let mut channel = || -> Result<u8> {
+                Ok(channels
+                    .next()
+                    .ok_or_else(|| anyhow!("GIMP palette color `{line}` has too few channels"))?
+                    .parse()?)
+            };
+
+            colors.push([channel()?, channel()?, channel()?]);
+        }
+
+        let len = colors.len() as u32;
+        let palette = Palette::from_colors(colors, columns.unwrap_or(len));
+
+        palette.validate(load_context.path())?;
+        Ok(palette)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gpl"]
+    }
+}
+
+/// Loads palettes from lists of hex colors (`.hex`), one per line. Also accepts Paint.NET
+/// palettes, where lines starting with `;` are comments, and colors are `AARRGGBB`.
+/// The palette is laid out in a single row for filters.
+#[derive(Default)]
+struct HexPaletteLoader;
+
+impl AssetLoader for HexPaletteLoader {
+    type Asset = Palette;
+    type Settings = ();
+    type Error = Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        &(): &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Palette> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let text = std::str::from_utf8(&bytes)?;
+
+        let colors = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with(';'))
+            .map(|line| {
+                let hex = line.trim_start_matches('#');
+                let rgb = match hex.len() {
+                    6 => hex,
+                    8 => &hex[2..],
+                    _ => return Err(anyhow!("invalid hex color `{line}`")),
+                };
+                let color = u32::from_str_radix(rgb, 16)?;
+
+                Ok([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let len = colors.len() as u32;
+        let palette = Palette::from_colors(colors, len);
+
+        palette.validate(load_context.path())?;
+        Ok(palette)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["hex"]
+    }
+}
+
 /// A palette. Palettes are loaded from images containing pixels
 /// that represent what colors the game may display. You may use up to 255 colors.
-/// The top-left pixel in the palette is used as the background color.
+/// The top-left pixel in the palette is used as the background color,
+/// unless [`PxBackgroundColor`] is changed. Palettes may also be loaded from GIMP palettes
+/// (`.gpl`) and lists of hex colors (`.hex`), where the first color is the background color.
 #[derive(Asset, Clone, TypePath, Debug)]
 pub struct Palette {
     pub(crate) size: UVec2,
@@ -66,15 +183,41 @@ pub struct Palette {
     pub(crate) indices: HashMap<[u8; 3], u8>,
 }
 
+/// Draws a layer with an alternate palette instead of [`PaletteHandle`]'s. The alternate palette's
+/// pixels must be laid out the same as the original. Only one alternate palette can be displayed
+/// at a time, so if multiple are in use, the lowest layer's palette is used, layers with
+/// a different palette are drawn with the game's palette, and a warning is logged.
+#[derive(Component, Clone, Debug)]
+pub struct PxLayerPalette<L: PxLayer> {
+    /// The layer to draw with the alternate palette
+    pub layer: L,
+    /// The alternate palette
+    pub palette: Handle<Palette>,
+}
+
 /// Resource containing the game's palette. Set this resource
 /// to a new palette to change the game's palette. The replacement palette's pixels
-/// must be laid out the same as the original. You cannot change the palette that is used
-/// to load assets.
+/// must be laid out the same as the original. To change the palette that is used
+/// to load assets, use [`reload_assets_with_palette`].
 #[derive(Resource, Deref, DerefMut)]
 pub struct PaletteHandle(pub Handle<Palette>);
 
 pub(crate) type PaletteParam<'w> = AssetSingleton<'w, PaletteHandle>;
 
+/// Resource that crossfades the game's palette from one palette to another, overriding
+/// [`PaletteHandle`] while it exists. Colors are interpolated in linear RGB. Advance `progress`
+/// over time, and when the transition is done, set [`PaletteHandle`] to `to`
+/// and remove this resource. Both palettes' pixels must be laid out the same as the original.
+#[derive(Resource, Clone, Debug)]
+pub struct PxPaletteTransition {
+    /// The palette to transition from
+    pub from: Handle<Palette>,
+    /// The palette to transition to
+    pub to: Handle<Palette>,
+    /// How far along the transition is, from 0 (`from`) to 1 (`to`)
+    pub progress: f32,
+}
+
 #[derive(Resource, Deref)]
 struct LoadingAssetPaletteHandle(Handle<Palette>);
 
@@ -120,6 +263,88 @@ impl Palette {
     }
 }
 
+impl Palette {
+    /// The color at the given index. Panics if the index is out of bounds.
+    pub fn color(&self, index: u8) -> [u8; 3] {
+        self.colors[index as usize]
+    }
+
+    /// The index of the given color, or `None` if it isn't in the palette
+    pub fn index_of(&self, color: Color) -> Option<u8> {
+        let [r, g, b, _] = color.to_srgba().to_u8_array();
+        self.indices.get(&[r, g, b]).copied()
+    }
+
+    /// The index of the color that looks most similar to the given color, compared in Oklab.
+    /// Returns 0 if the palette is empty.
+    pub fn nearest(&self, color: Color) -> u8 {
+        let oklab = |color: Oklaba| Vec3::new(color.lightness, color.a, color.b);
+        let target = oklab(color.into());
+
+        self.colors
+            .iter()
+            .map(|&[r, g, b]| oklab(Srgba::rgb_u8(r, g, b).into()).distance_squared(target))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(index, _)| index as u8)
+    }
+
+    // Errors if the palette has no colors or too many colors, and warns about duplicate colors,
+    // since only the last index of each color is used when loading assets
+    fn validate(&self, path: &Path) -> Result<()> {
+        if self.colors.is_empty() {
+            return Err(anyhow!("palette `{}` has no colors", path.display()));
+        }
+
+        if self.colors.len() > 255 {
+            return Err(anyhow!(
+                "palette `{}` has {} colors, but may have at most 255",
+                path.display(),
+                self.colors.len(),
+            ));
+        }
+
+        let mut indices = HashMap::<_, Vec<_>>::new();
+        for (index, &color) in self.colors.iter().enumerate() {
+            indices.entry(color).or_default().push(index);
+        }
+
+        let mut duplicates = indices
+            .into_iter()
+            .filter(|(_, indices)| indices.len() > 1)
+            .map(|([r, g, b], indices)| format!("#{r:02x}{g:02x}{b:02x} at indices {indices:?}"))
+            .collect::<Vec<_>>();
+
+        if !duplicates.is_empty() {
+            duplicates.sort_unstable();
+            warn!(
+                "palette `{}` has duplicate colors, which load as the same index: {}",
+                path.display(),
+                duplicates.join(", "),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Create a palette from a list of colors. `columns` is the width of the palette's layout,
+    /// which filters must match.
+    pub fn from_colors(colors: impl IntoIterator<Item = [u8; 3]>, columns: u32) -> Palette {
+        let colors = colors.into_iter().collect::<Vec<_>>();
+        let columns = columns.max(1);
+
+        Palette {
+            size: UVec2::new(columns, (colors.len() as u32).div_ceil(columns)),
+            indices: colors
+                .iter()
+                .enumerate()
+                .map(|(i, color)| (*color, i as u8))
+                .collect(),
+            colors,
+        }
+    }
+}
+
 fn init_palette(path: PathBuf) -> impl Fn(Commands, Res<AssetServer>) {
     move |mut commands, assets| {
         let palette = assets.load(path.clone());
@@ -128,49 +353,75 @@ fn init_palette(path: PathBuf) -> impl Fn(Commands, Res<AssetServer>) {
     }
 }
 
-/// # Safety
-///
-/// Must not be read before `ASSET_PALETTE_INITIALIZED` is set. Must not be mutated after
-/// `ASSET_PALETTE_INITIALIZED` is set.
-static mut ASSET_PALETTE: Option<Palette> = None;
-/// Must not be unset after it has been set
-static ASSET_PALETTE_INITIALIZED: AtomicBool = AtomicBool::new(false);
-/// Notifies after `ASSET_PALETTE_INITIALIZED` is set
+/// The palette used to load assets. Each palette is leaked, so references to it stay valid
+/// if the palette is replaced by [`reload_assets_with_palette`].
+static ASSET_PALETTE: RwLock<Option<&'static Palette>> = RwLock::new(None);
+/// Notifies after `ASSET_PALETTE` is set
 static ASSET_PALETTE_JUST_INITIALIZED: Event = Event::new();
 
-#[allow(static_mut_refs)]
 pub(crate) async fn asset_palette() -> &'static Palette {
-    if ASSET_PALETTE_INITIALIZED.load(Ordering::SeqCst) {
-        // SAFETY: Checked above
-        return unsafe { ASSET_PALETTE.as_ref() }.unwrap();
-    }
+    loop {
+        if let Some(palette) = *ASSET_PALETTE.read().unwrap() {
+            return palette;
+        }
 
-    let just_initialized = ASSET_PALETTE_JUST_INITIALIZED.listen();
+        let just_initialized = ASSET_PALETTE_JUST_INITIALIZED.listen();
 
-    if ASSET_PALETTE_INITIALIZED.load(Ordering::SeqCst) {
-        // SAFETY: Checked above
-        return unsafe { ASSET_PALETTE.as_ref() }.unwrap();
-    }
+        if let Some(palette) = *ASSET_PALETTE.read().unwrap() {
+            return palette;
+        }
 
-    just_initialized.await;
-    // SAFETY: `just_initialized` finished waiting, so `ASSET_PALETTE_INITIALIZED` is set
-    unsafe { ASSET_PALETTE.as_ref() }.unwrap()
+        just_initialized.await;
+    }
 }
 
-fn load_asset_palette(palette: LoadingAssetPaletteParam, mut cmd: Commands) {
+fn load_asset_palette(
+    palette: LoadingAssetPaletteParam,
+    reload: Option<Res<ReloadAssets>>,
+    mut cmd: Commands,
+) {
     let Some(palette) = palette.get() else {
         return;
     };
 
-    if ASSET_PALETTE_INITIALIZED.load(Ordering::SeqCst) {
-        panic!("Tried to set the asset palette after it was initialized");
-    }
-
-    let palette = Some(palette.clone());
-    // SAFETY: Checked above
-    unsafe { ASSET_PALETTE = palette };
-    ASSET_PALETTE_INITIALIZED.store(true, Ordering::SeqCst);
+    *ASSET_PALETTE.write().unwrap() = Some(Box::leak(Box::new(palette.clone())));
     ASSET_PALETTE_JUST_INITIALIZED.notify(usize::MAX);
 
     cmd.remove_resource::<LoadingAssetPaletteHandle>();
+
+    if reload.is_some() {
+        cmd.remove_resource::<ReloadAssets>();
+        cmd.queue(reload_assets);
+    }
+}
+
+#[derive(Resource)]
+struct ReloadAssets;
+
+/// Changes the palette that is used to load assets, and reloads every loaded sprite, filter,
+/// tileset, and typeface with it once it's loaded. Queue it with [`Commands::queue`].
+/// Usually, you will also want to set [`PaletteHandle`] to the new palette. Assets loaded
+/// while the new palette is loading may use either palette.
+pub fn reload_assets_with_palette(palette: Handle<Palette>) -> impl FnOnce(&mut World) {
+    move |world| {
+        world.insert_resource(LoadingAssetPaletteHandle(palette));
+        world.insert_resource(ReloadAssets);
+    }
+}
+
+fn reload_assets(world: &mut World) {
+    fn reload<A: Asset>(world: &World) {
+        let server = world.resource::<AssetServer>();
+
+        for id in world.resource::<Assets<A>>().ids() {
+            if let Some(path) = server.get_path(id) {
+                server.reload(path.into_owned());
+            }
+        }
+    }
+
+    reload::<PxSpriteAsset>(world);
+    reload::<PxFilterAsset>(world);
+    reload::<PxTileset>(world);
+    reload::<PxTypeface>(world);
 }
@@ -2,12 +2,17 @@
 
 use std::{
     fmt::{Debug, Formatter, Result},
+    ops::RangeInclusive,
     time::Duration,
 };
 
-use bevy::{ecs::system::EntityCommands, utils::Instant};
+use bevy::{
+    ecs::system::EntityCommands,
+    utils::{HashMap, Instant},
+};
 
 use crate::{
+    animation::Animation,
     position::{DefaultLayer, PxLayer},
     prelude::*,
     set::PxSet,
@@ -20,15 +25,25 @@ const TIME_OFFSET: Duration = Duration::from_secs(60 * 60 * 24);
 
 pub(crate) fn plug<L: PxLayer>(app: &mut App) {
     app.add_systems(
+        PreUpdate,
+        delay_paused_particles.run_if(resource_equals(PxTimePaused(true))),
+    )
+    .add_systems(
         PostUpdate,
         (
             (
-                (simulate_emitters::<L>, insert_emitter_time),
+                (simulate_emitters::<L>, insert_emitter_time::<L>),
                 (apply_deferred, update_emitters::<L>)
                     .chain()
                     .in_set(PxSet::UpdateEmitters),
             )
                 .chain(),
+            (
+                despawn_emitters,
+                (offset_particle_animations, apply_particle_anim_phases).chain(),
+            )
+                .after(PxSet::UpdateEmitters),
+            fade_particles,
             despawn_particles,
         ),
     );
@@ -50,6 +65,37 @@ impl From<Duration> for PxParticleLifetime {
     }
 }
 
+/// Fades a particle out over the end of its lifetime by lowering its [`PxOpacity`]
+#[derive(Clone, Component, Copy, Debug)]
+#[require(PxOpacity)]
+pub struct PxParticleFade {
+    /// Fraction of the particle's lifetime, from 0 to 1, after which it starts fading.
+    /// It is fully transparent when its lifetime ends.
+    pub start: f32,
+}
+
+impl Default for PxParticleFade {
+    fn default() -> Self {
+        Self { start: 0. }
+    }
+}
+
+impl PxParticleFade {
+    fn opacity(self, age: Duration, lifetime: Duration) -> PxOpacity {
+        let progress = age.as_secs_f32() / lifetime.as_secs_f32();
+
+        PxOpacity(if progress <= self.start {
+            1.
+        } else {
+            ((1. - progress) / (1. - self.start)).clamp(0., 1.)
+        })
+    }
+}
+
+/// The emitter that spawned a particle
+#[derive(Clone, Component, Copy, Debug, Deref, DerefMut)]
+pub struct PxParticleEmitter(pub Entity);
+
 /// Spawn frequency range for an emitter
 #[derive(Debug)]
 pub struct PxEmitterFrequency {
@@ -83,11 +129,15 @@ impl PxEmitterFrequency {
         }
     }
 
+    fn sample(&self, rng: &mut Rng) -> Duration {
+        (self.max - self.min).mul_f32(rng.f32()) + self.min
+    }
+
     fn next(&mut self, rng: &mut Rng) -> Duration {
         if let Some(duration) = self.next {
             duration
         } else {
-            let duration = (self.max - self.min).mul_f32(rng.f32()) + self.min;
+            let duration = self.sample(rng);
             self.next = Some(duration);
             duration
         }
@@ -108,24 +158,190 @@ pub enum PxEmitterSimulation {
     #[default]
     None,
     /// The emitter is pre-simulated. This means that the emitter will spawn particles
-    /// as soon as the `PxEmitterBundle` is spawned, with values as if they had been spawned
+    /// as soon as the [`PxEmitter`] is spawned, with values as if they had been spawned
     /// earlier. This is useful when an emitter comes into view,
     /// and you want it to look like it had been emitting particles all along.
     Simulate,
 }
 
-/// Creates a particle emitter
+/// Area in which an emitter spawns its particles. Positions are sampled uniformly.
+#[derive(Clone, Copy, Debug)]
+pub enum PxEmitterShape {
+    /// A rectangle. Both corners are inclusive.
+    Rect(IRect),
+    /// A filled circle
+    Circle {
+        /// Center of the circle
+        center: IVec2,
+        /// Radius of the circle
+        radius: f32,
+    },
+    /// The area between two concentric circles
+    Ring {
+        /// Center of the circles
+        center: IVec2,
+        /// Radius of the inner circle
+        inner: f32,
+        /// Radius of the outer circle
+        outer: f32,
+    },
+    /// A line segment between two points
+    Line {
+        /// One end of the line
+        a: IVec2,
+        /// The other end of the line
+        b: IVec2,
+    },
+}
+
+impl Default for PxEmitterShape {
+    fn default() -> Self {
+        Self::Rect(default())
+    }
+}
+
+impl From<IRect> for PxEmitterShape {
+    fn from(rect: IRect) -> Self {
+        Self::Rect(rect)
+    }
+}
+
+impl PxEmitterShape {
+    fn sample(self, rng: &mut Rng) -> IVec2 {
+        match self {
+            Self::Rect(rect) => IVec2::new(
+                rng.i32(rect.min.x..=rect.max.x),
+                rng.i32(rect.min.y..=rect.max.y),
+            ),
+            Self::Circle { center, radius } => sample_ring(rng, center, 0., radius),
+            Self::Ring {
+                center,
+                inner,
+                outer,
+            } => sample_ring(rng, center, inner, outer),
+            Self::Line { a, b } => (a.as_vec2() + (b - a).as_vec2() * rng.f32())
+                .round()
+                .as_ivec2(),
+        }
+    }
+}
+
+fn sample_ring(rng: &mut Rng, center: IVec2, inner: f32, outer: f32) -> IVec2 {
+    // Taking the square root keeps the distribution uniform over the area
+    let distance = (inner * inner + (outer * outer - inner * inner) * rng.f32()).sqrt();
+    let angle = std::f32::consts::TAU * rng.f32();
+
+    (center.as_vec2() + Vec2::from_angle(angle) * distance)
+        .round()
+        .as_ivec2()
+}
+
+/// Determines the initial velocity of an emitter's particles
+#[derive(Clone, Debug)]
+pub enum PxEmitterVelocity {
+    /// Every particle starts with this velocity
+    Fixed(Vec2),
+    /// Each particle is launched in a random direction within a cone, at a random speed
+    Cone {
+        /// Direction of the center of the cone. Does not need to be normalized.
+        direction: Vec2,
+        /// Maximum angle, in radians, between a particle's direction and `direction`
+        spread: f32,
+        /// Range of possible speeds
+        speed: RangeInclusive<f32>,
+    },
+}
+
+impl Default for PxEmitterVelocity {
+    fn default() -> Self {
+        Self::Fixed(Vec2::ZERO)
+    }
+}
+
+impl From<Vec2> for PxEmitterVelocity {
+    fn from(velocity: Vec2) -> Self {
+        Self::Fixed(velocity)
+    }
+}
+
+impl PxEmitterVelocity {
+    fn sample(&self, rng: &mut Rng) -> Vec2 {
+        match self {
+            &Self::Fixed(velocity) => velocity,
+            Self::Cone {
+                direction,
+                spread,
+                speed,
+            } => {
+                let angle = direction.to_angle() + spread * rng.f32_normalized();
+                let speed = speed.start() + (speed.end() - speed.start()) * rng.f32();
+
+                Vec2::from_angle(angle) * speed
+            }
+        }
+    }
+}
+
+/// Determines how an emitter spawns its particles
+#[derive(Debug, Default, Eq, PartialEq)]
+pub enum PxEmitterMode {
+    /// The emitter spawns particles continuously, according to its [`PxEmitterFrequency`]
+    #[default]
+    Continuous,
+    /// The emitter spawns `count` particles at once when it is spawned, and then stops.
+    /// Its [`PxEmitterFrequency`] and [`PxEmitterSimulation`] are ignored.
+    Burst {
+        /// Number of particles to spawn
+        count: u32,
+        /// Whether to despawn the emitter after the burst
+        despawn: bool,
+    },
+}
+
+/// Creates a particle emitter. Particles start with the emitter's [`PxAcceleration`]
+/// if it has one. The emitter itself isn't moved by its [`PxVelocity`] or [`PxAcceleration`].
 #[derive(Component)]
-#[require(PxAnchor, DefaultLayer, PxCanvas, PxParticleLifetime, PxVelocity)]
+#[require(PxAnchor, DefaultLayer, PxCanvas, PxParticleLifetime)]
 pub struct PxEmitter {
     /// Possible sprites for an emitter's particles
     pub sprites: Vec<Handle<PxSpriteAsset>>,
+    /// Possible filters for an emitter's particles. If empty, particles are not filtered.
+    pub filters: Vec<Handle<PxFilterAsset>>,
     /// Location range for an emitter's particles
-    pub range: IRect,
+    pub range: PxEmitterShape,
+    /// If true, `range` is relative to the emitter's [`PxSubPosition`], or its [`PxPosition`]
+    /// if it doesn't have one, at the time each particle spawns
+    pub local: bool,
     /// A [`PxEmitterFrequency`]
     pub frequency: PxEmitterFrequency,
     /// A [`PxEmitterSimulation`]
     pub simulation: PxEmitterSimulation,
+    /// A [`PxEmitterMode`]
+    pub mode: PxEmitterMode,
+    /// A [`PxEmitterVelocity`]. If `None`, particles start with the emitter's [`PxVelocity`],
+    /// or with no velocity if it doesn't have one.
+    pub velocity: Option<PxEmitterVelocity>,
+    /// Each particle's velocity is offset from its sampled velocity by a random amount,
+    /// up to this much on each axis
+    pub velocity_jitter: Vec2,
+    /// Each particle's lifetime is extended past the emitter's [`PxParticleLifetime`]
+    /// by a random amount, up to this much
+    pub lifetime_jitter: Duration,
+    /// If set, a continuous emitter skips spawning particles while it has this many
+    /// live particles
+    pub max_particles: Option<usize>,
+    /// If set, particles fade out near the end of their lifetime
+    pub fade: Option<PxParticleFade>,
+    /// If set, the emitter stops emitting and is despawned this long after it's spawned.
+    /// If `None`, it emits forever.
+    pub lifetime: Option<Duration>,
+    /// If true, an emitter with a `lifetime` waits for its live particles to expire
+    /// before it's despawned
+    pub despawn_after_particles: bool,
+    /// If true, each particle's [`PxAnimation`] starts at a random point in the animation,
+    /// so particles with the same animated sprite don't animate in sync. The animation
+    /// may be added in `on_spawn`.
+    pub random_anim_phase: bool,
     /// This function is run on each particle that spawns. It is run
     /// after all of the other components are added, so you can use this to override components.
     pub on_spawn: Box<dyn Fn(&mut EntityCommands) + Send + Sync>,
@@ -135,9 +351,20 @@ impl Default for PxEmitter {
     fn default() -> Self {
         Self {
             sprites: Vec::new(),
+            filters: Vec::new(),
             range: default(),
+            local: false,
             frequency: default(),
             simulation: default(),
+            mode: default(),
+            velocity: None,
+            velocity_jitter: Vec2::ZERO,
+            lifetime_jitter: Duration::ZERO,
+            max_particles: None,
+            fade: None,
+            lifetime: None,
+            despawn_after_particles: false,
+            random_anim_phase: false,
             on_spawn: Box::new(|_| ()),
         }
     }
@@ -147,20 +374,172 @@ impl Debug for PxEmitter {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         f.debug_struct("PxEmitter")
             .field("sprites", &self.sprites)
+            .field("filters", &self.filters)
             .field("range", &self.range)
+            .field("local", &self.local)
             .field("frequency", &self.frequency)
             .field("simulation", &self.simulation)
+            .field("mode", &self.mode)
+            .field("velocity", &self.velocity)
+            .field("velocity_jitter", &self.velocity_jitter)
+            .field("lifetime_jitter", &self.lifetime_jitter)
+            .field("max_particles", &self.max_particles)
+            .field("fade", &self.fade)
+            .field("lifetime", &self.lifetime)
+            .field("despawn_after_particles", &self.despawn_after_particles)
+            .field("random_anim_phase", &self.random_anim_phase)
             .field("on_spawn", &())
             .finish()
     }
 }
 
+impl PxEmitter {
+    /// Predicts where this emitter's particles would be after it emits for `duration`,
+    /// without spawning any. `lifetime`, `velocity`, and `acceleration` are the emitter's
+    /// [`PxParticleLifetime`], [`PxVelocity`], and [`PxAcceleration`]. `velocity` is only used
+    /// if the emitter's `velocity` field is `None`. Positions are relative to the emitter
+    /// if it's `local`. Particles that would have expired by then are left out.
+    pub fn simulate_positions(
+        &self,
+        duration: Duration,
+        lifetime: PxParticleLifetime,
+        velocity: Option<PxVelocity>,
+        acceleration: Vec2,
+        rng: &mut Rng,
+    ) -> Vec<Vec2> {
+        let mut positions = Vec::new();
+
+        match self.mode {
+            PxEmitterMode::Burst { count, .. } => {
+                for _ in 0..count {
+                    positions.extend(self.simulate_position(
+                        duration,
+                        lifetime,
+                        velocity.as_ref(),
+                        acceleration,
+                        rng,
+                    ));
+                }
+            }
+            PxEmitterMode::Continuous => {
+                let mut age = Duration::ZERO;
+
+                while age <= duration
+                    && self
+                        .max_particles
+                        .is_none_or(|max_particles| positions.len() < max_particles)
+                {
+                    positions.extend(self.simulate_position(
+                        age,
+                        lifetime,
+                        velocity.as_ref(),
+                        acceleration,
+                        rng,
+                    ));
+
+                    let interval = self.frequency.sample(rng);
+                    if interval.is_zero() {
+                        break;
+                    }
+                    age += interval;
+                }
+            }
+        }
+
+        positions
+    }
+
+    // Position of a particle that has existed for `age`, or `None` if it would have expired
+    fn simulate_position(
+        &self,
+        age: Duration,
+        lifetime: PxParticleLifetime,
+        emitter_velocity: Option<&PxVelocity>,
+        acceleration: Vec2,
+        rng: &mut Rng,
+    ) -> Option<Vec2> {
+        let velocity = self.sample_velocity(emitter_velocity, rng);
+        let lifetime = self.sample_lifetime(lifetime, rng);
+        let position = self.sample_position(None, None, rng);
+
+        (age <= *lifetime).then(|| project_motion(position, *velocity, acceleration, age).0)
+    }
+
+    fn expired(&self, spawned: Instant, current_time: Instant) -> bool {
+        self.lifetime
+            .is_some_and(|lifetime| current_time - spawned >= lifetime)
+    }
+
+    fn sample_position(
+        &self,
+        position: Option<&PxPosition>,
+        sub_position: Option<&PxSubPosition>,
+        rng: &mut Rng,
+    ) -> Vec2 {
+        let range = self.range.sample(rng).as_vec2();
+
+        if !self.local {
+            return range;
+        }
+
+        range
+            + match (sub_position, position) {
+                (Some(sub_position), _) => **sub_position,
+                (None, Some(position)) => position.as_vec2(),
+                (None, None) => Vec2::ZERO,
+            }
+    }
+
+    fn sample_velocity(&self, fallback: Option<&PxVelocity>, rng: &mut Rng) -> PxVelocity {
+        let velocity = match &self.velocity {
+            Some(velocity) => velocity.sample(rng),
+            None => fallback.map_or(Vec2::ZERO, |velocity| **velocity),
+        };
+
+        PxVelocity(
+            velocity + self.velocity_jitter * Vec2::new(rng.f32_normalized(), rng.f32_normalized()),
+        )
+    }
+
+    fn sample_lifetime(&self, lifetime: PxParticleLifetime, rng: &mut Rng) -> PxParticleLifetime {
+        PxParticleLifetime(*lifetime + self.lifetime_jitter.mul_f32(rng.f32()))
+    }
+
+    fn sample_filter(&self, rng: &mut Rng) -> Option<PxFilter> {
+        rng.sample(&self.filters).cloned().map(PxFilter)
+    }
+}
+
+// Position and velocity after moving for `age` under constant acceleration
+fn project_motion(
+    position: Vec2,
+    velocity: Vec2,
+    acceleration: Vec2,
+    age: Duration,
+) -> (Vec2, Vec2) {
+    let age = age.as_secs_f32();
+
+    (
+        position + velocity * age + 0.5 * acceleration * age * age,
+        velocity + acceleration * age,
+    )
+}
+
 #[derive(Component, Debug, Deref, DerefMut)]
 struct PxEmitterStart(Instant);
 
+// When the emitter was spawned, for its `lifetime`
+#[derive(Component, Debug, Deref, DerefMut)]
+struct PxEmitterSpawned(Instant);
+
 #[derive(Component, Debug, Deref, DerefMut)]
 struct PxParticleStart(Instant);
 
+// Fraction of the way through its animation that a particle's animation should start.
+// Applied once the particle's sprite is loaded, since per-frame durations need its frame count.
+#[derive(Component, Debug, Deref, DerefMut)]
+struct PxParticleAnimPhase(f32);
+
 impl Default for PxParticleStart {
     fn default() -> Self {
         Self(Instant::now())
@@ -173,67 +552,123 @@ impl From<Instant> for PxParticleStart {
     }
 }
 
-#[derive(Bundle, Default)]
-struct PxParticleBundle {
-    position: PxSubPosition,
-    velocity: PxVelocity,
-    start: PxParticleStart,
-    lifetime: PxParticleLifetime,
+// Pushes particles' and emitters' start times forward by the time spent paused,
+// so particles don't age and emitters don't spawn while paused
+fn delay_paused_particles(
+    mut particles: Query<&mut PxParticleStart>,
+    mut emitters: Query<(&mut PxEmitterStart, &mut PxEmitterSpawned)>,
+    time: Res<Time<Real>>,
+) {
+    for mut start in &mut particles {
+        **start += time.delta();
+    }
+
+    for (mut start, mut spawned) in &mut emitters {
+        **start += time.delta();
+        **spawned += time.delta();
+    }
 }
 
 fn simulate_emitters<L: PxLayer>(
     mut commands: Commands,
     emitters: Query<
         (
+            Entity,
             &PxEmitter,
             &PxAnchor,
             &L,
             &PxCanvas,
             &PxParticleLifetime,
-            &PxVelocity,
+            Option<&PxVelocity>,
+            Option<&PxAcceleration>,
+            Option<&PxPosition>,
+            Option<&PxSubPosition>,
         ),
         Added<PxEmitter>,
     >,
     time: Res<Time<Real>>,
     mut rng: ResMut<GlobalRng>,
 ) {
-    for (emitter, anchor, layer, canvas, lifetime, velocity) in &emitters {
-        if emitter.simulation != PxEmitterSimulation::Simulate {
+    for (
+        id,
+        emitter,
+        anchor,
+        layer,
+        canvas,
+        lifetime,
+        emitter_velocity,
+        acceleration,
+        position,
+        sub_position,
+    ) in &emitters
+    {
+        if emitter.simulation != PxEmitterSimulation::Simulate
+            || emitter.mode != PxEmitterMode::Continuous
+        {
             continue;
         }
 
         let current_time = time.last_update().unwrap_or_else(|| time.startup()) + TIME_OFFSET;
         let mut simulated_time = current_time;
+        let mut count = 0;
 
-        while simulated_time + **lifetime >= current_time {
-            let position = IVec2::new(
-                rng.i32(emitter.range.min.x..=emitter.range.max.x),
-                rng.i32(emitter.range.min.y..=emitter.range.max.y),
-            )
-            .as_vec2()
-                + **velocity * (current_time - simulated_time).as_secs_f32();
-
-            (emitter.on_spawn)(&mut commands.spawn((
-                PxSprite(rng.sample(&emitter.sprites).unwrap().clone()),
-                PxPosition::from(IVec2::new(
-                    position.x.round() as i32,
-                    position.y.round() as i32,
-                )),
-                *anchor,
-                layer.clone(),
-                *canvas,
-                PxSubPosition::from(position),
+        while simulated_time + **lifetime + emitter.lifetime_jitter >= current_time
+            && emitter
+                .max_particles
+                .is_none_or(|max_particles| count < max_particles)
+        {
+            let age = current_time - simulated_time;
+            let velocity = emitter.sample_velocity(emitter_velocity, rng.get_mut());
+            let lifetime = emitter.sample_lifetime(*lifetime, rng.get_mut());
+            let filter = emitter.sample_filter(rng.get_mut());
+            let acceleration = acceleration.map_or(Vec2::ZERO, |acceleration| **acceleration);
+            let (position, velocity) = project_motion(
+                emitter.sample_position(position, sub_position, rng.get_mut()),
                 *velocity,
-                PxParticleStart::from(simulated_time),
-                *lifetime,
-                Name::new("Particle"),
-            )));
+                acceleration,
+                age,
+            );
+            let velocity = PxVelocity(velocity);
+
+            // With lifetime jitter, some of the earliest particles would already be gone
+            if age <= *lifetime {
+                let mut particle = commands.spawn((
+                    PxSprite(rng.sample(&emitter.sprites).unwrap().clone()),
+                    PxPosition::from(IVec2::new(
+                        position.x.round() as i32,
+                        position.y.round() as i32,
+                    )),
+                    *anchor,
+                    layer.clone(),
+                    *canvas,
+                    PxSubPosition::from(position),
+                    velocity,
+                    PxParticleStart::from(simulated_time),
+                    lifetime,
+                    PxParticleEmitter(id),
+                    Name::new("Particle"),
+                ));
+                count += 1;
+
+                if acceleration != Vec2::ZERO {
+                    particle.insert(PxAcceleration(acceleration));
+                }
+
+                if let Some(filter) = filter {
+                    particle.insert(filter);
+                }
+
+                if let Some(fade) = emitter.fade {
+                    particle.insert((fade, fade.opacity(age, *lifetime)));
+                }
+
+                (emitter.on_spawn)(&mut particle);
+            }
 
             // In wasm, the beginning of time is the start of the program, so we `checked_sub`
-            let Some(new_time) = simulated_time.checked_sub(
-                (emitter.frequency.max - emitter.frequency.min).mul_f32(rng.f32())
-                    + emitter.frequency.min,
-            ) else {
+            let Some(new_time) =
+                simulated_time.checked_sub(emitter.frequency.sample(rng.get_mut()))
+            else {
                 break;
             };
             simulated_time = new_time;
@@ -241,15 +676,87 @@ fn simulate_emitters<L: PxLayer>(
     }
 }
 
-fn insert_emitter_time(
+fn insert_emitter_time<L: PxLayer>(
     mut commands: Commands,
-    emitters: Query<Entity, Added<PxEmitter>>,
+    emitters: Query<
+        (
+            Entity,
+            &PxEmitter,
+            &PxAnchor,
+            &L,
+            &PxCanvas,
+            &PxParticleLifetime,
+            Option<&PxVelocity>,
+            Option<&PxAcceleration>,
+            Option<&PxPosition>,
+            Option<&PxSubPosition>,
+        ),
+        Added<PxEmitter>,
+    >,
     time: Res<Time<Real>>,
     mut rng: ResMut<GlobalRng>,
 ) {
-    for emitter in &emitters {
-        commands.entity(emitter).insert((
-            PxEmitterStart(time.last_update().unwrap_or_else(|| time.startup()) + TIME_OFFSET),
+    let current_time = time.last_update().unwrap_or_else(|| time.startup()) + TIME_OFFSET;
+
+    for (
+        id,
+        emitter,
+        anchor,
+        layer,
+        canvas,
+        lifetime,
+        emitter_velocity,
+        acceleration,
+        position,
+        sub_position,
+    ) in &emitters
+    {
+        if let PxEmitterMode::Burst { count, despawn } = emitter.mode {
+            for _ in 0..count {
+                let position = emitter.sample_position(position, sub_position, rng.get_mut());
+
+                let velocity = emitter.sample_velocity(emitter_velocity, rng.get_mut());
+                let lifetime = emitter.sample_lifetime(*lifetime, rng.get_mut());
+                let filter = emitter.sample_filter(rng.get_mut());
+
+                let mut particle = commands.spawn((
+                    PxSprite(rng.sample(&emitter.sprites).unwrap().clone()),
+                    PxPosition::from(position.round().as_ivec2()),
+                    *anchor,
+                    layer.clone(),
+                    *canvas,
+                    PxSubPosition::from(position),
+                    velocity,
+                    PxParticleStart::from(current_time),
+                    lifetime,
+                    PxParticleEmitter(id),
+                    Name::new("Particle"),
+                ));
+
+                if let Some(&acceleration) = acceleration {
+                    particle.insert(acceleration);
+                }
+
+                if let Some(filter) = filter {
+                    particle.insert(filter);
+                }
+
+                if let Some(fade) = emitter.fade {
+                    particle.insert(fade);
+                }
+
+                (emitter.on_spawn)(&mut particle);
+            }
+
+            if despawn {
+                commands.entity(id).despawn();
+                continue;
+            }
+        }
+
+        commands.entity(id).insert((
+            PxEmitterStart(current_time),
+            PxEmitterSpawned(current_time),
             RngComponent::from(&mut rng),
         ));
     }
@@ -258,46 +765,201 @@ fn insert_emitter_time(
 fn update_emitters<L: PxLayer>(
     mut commands: Commands,
     mut emitters: Query<(
+        Entity,
         &mut PxEmitter,
         &PxAnchor,
         &L,
         &PxCanvas,
         &PxParticleLifetime,
-        &PxVelocity,
+        Option<&PxVelocity>,
+        Option<&PxAcceleration>,
+        Option<&PxPosition>,
+        Option<&PxSubPosition>,
         &mut PxEmitterStart,
+        &PxEmitterSpawned,
         &mut RngComponent,
     )>,
+    particles: Query<&PxParticleEmitter>,
     time: Res<Time<Real>>,
 ) {
-    for (mut emitter, anchor, layer, canvas, lifetime, velocity, mut start, mut rng) in
-        &mut emitters
+    let current_time = time.last_update().unwrap_or_else(|| time.startup()) + TIME_OFFSET;
+
+    let mut counts = HashMap::<Entity, usize>::new();
+    for &PxParticleEmitter(emitter) in &particles {
+        *counts.entry(emitter).or_default() += 1;
+    }
+
+    for (
+        id,
+        mut emitter,
+        anchor,
+        layer,
+        canvas,
+        lifetime,
+        emitter_velocity,
+        acceleration,
+        position,
+        sub_position,
+        mut start,
+        spawned,
+        mut rng,
+    ) in &mut emitters
     {
-        if time.last_update().unwrap_or_else(|| time.startup()) + TIME_OFFSET - **start
-            < emitter.frequency.next(rng.get_mut())
-        {
+        if emitter.mode != PxEmitterMode::Continuous || emitter.expired(**spawned, current_time) {
+            continue;
+        }
+
+        if current_time - **start < emitter.frequency.next(rng.get_mut()) {
             continue;
         }
 
         **start += emitter.frequency.update_next(rng.get_mut());
-        let position = IVec2::new(
-            rng.i32(emitter.range.min.x..=emitter.range.max.x),
-            rng.i32(emitter.range.min.y..=emitter.range.max.y),
-        );
 
-        (emitter.on_spawn)(&mut commands.spawn((
+        if emitter.max_particles.is_some_and(|max_particles| {
+            counts.get(&id).copied().unwrap_or_default() >= max_particles
+        }) {
+            continue;
+        }
+
+        let position = emitter.sample_position(position, sub_position, rng.get_mut());
+
+        let velocity = emitter.sample_velocity(emitter_velocity, rng.get_mut());
+        let lifetime = emitter.sample_lifetime(*lifetime, rng.get_mut());
+        let filter = emitter.sample_filter(rng.get_mut());
+
+        let mut particle = commands.spawn((
             PxSprite(rng.sample(&emitter.sprites).unwrap().clone()),
-            PxPosition::from(position),
+            PxPosition::from(position.round().as_ivec2()),
             *anchor,
             layer.clone(),
             *canvas,
-            PxSubPosition::from(position.as_vec2()),
-            *velocity,
-            PxParticleStart::from(
-                time.last_update().unwrap_or_else(|| time.startup()) + TIME_OFFSET,
-            ),
-            *lifetime,
+            PxSubPosition::from(position),
+            velocity,
+            PxParticleStart::from(current_time),
+            lifetime,
+            PxParticleEmitter(id),
             Name::new("Particle"),
-        )));
+        ));
+
+        if let Some(&acceleration) = acceleration {
+            particle.insert(acceleration);
+        }
+
+        if let Some(filter) = filter {
+            particle.insert(filter);
+        }
+
+        if let Some(fade) = emitter.fade {
+            particle.insert(fade);
+        }
+
+        (emitter.on_spawn)(&mut particle);
+    }
+}
+
+// Moves new particles' animations back by the particle's age if it was pre-simulated,
+// and gives them a random phase to apply
+fn offset_particle_animations(
+    mut commands: Commands,
+    mut particles: Query<
+        (
+            Entity,
+            &mut PxAnimation,
+            &PxParticleStart,
+            &PxParticleEmitter,
+        ),
+        Added<PxAnimation>,
+    >,
+    emitters: Query<&PxEmitter>,
+    time: Res<Time<Real>>,
+    mut rng: ResMut<GlobalRng>,
+) {
+    let current_time = time.last_update().unwrap_or_else(|| time.startup()) + TIME_OFFSET;
+
+    for (id, mut animation, start, &PxParticleEmitter(emitter)) in &mut particles {
+        if !emitters
+            .get(emitter)
+            .is_ok_and(|emitter| emitter.random_anim_phase)
+        {
+            continue;
+        }
+
+        // In wasm, the beginning of time is the start of the program, so we `checked_sub`
+        if let Some(start) = animation
+            .start
+            .checked_sub(current_time.saturating_duration_since(**start))
+        {
+            animation.start = start;
+        }
+
+        commands.entity(id).insert(PxParticleAnimPhase(rng.f32()));
+    }
+}
+
+// Moves particles' animations back by their random phase, once their sprites are loaded
+fn apply_particle_anim_phases(
+    mut commands: Commands,
+    mut particles: Query<(Entity, &mut PxAnimation, &PxSprite, &PxParticleAnimPhase)>,
+    sprites: Res<Assets<PxSpriteAsset>>,
+) {
+    for (id, mut animation, sprite, &PxParticleAnimPhase(phase)) in &mut particles {
+        let duration = match animation.duration {
+            PxAnimationDuration::PerAnimation(duration) => duration,
+            PxAnimationDuration::PerFrame(duration) => {
+                let Some(sprite) = sprites.get(&**sprite) else {
+                    continue;
+                };
+
+                duration * sprite.frame_count() as u32
+            }
+        };
+
+        if let Some(start) = animation.start.checked_sub(duration.mul_f32(phase)) {
+            animation.start = start;
+        }
+
+        commands.entity(id).remove::<PxParticleAnimPhase>();
+    }
+}
+
+fn despawn_emitters(
+    mut commands: Commands,
+    emitters: Query<(Entity, &PxEmitter, &PxEmitterSpawned)>,
+    particles: Query<&PxParticleEmitter>,
+    time: Res<Time<Real>>,
+) {
+    let current_time = time.last_update().unwrap_or_else(|| time.startup()) + TIME_OFFSET;
+
+    for (id, emitter, spawned) in &emitters {
+        if !emitter.expired(**spawned, current_time) {
+            continue;
+        }
+
+        if emitter.despawn_after_particles
+            && particles
+                .iter()
+                .any(|&PxParticleEmitter(particle_emitter)| particle_emitter == id)
+        {
+            continue;
+        }
+
+        commands.entity(id).despawn();
+    }
+}
+
+fn fade_particles(
+    mut particles: Query<(
+        &PxParticleFade,
+        &PxParticleLifetime,
+        &PxParticleStart,
+        &mut PxOpacity,
+    )>,
+    time: Res<Time<Real>>,
+) {
+    let current_time = time.last_update().unwrap_or_else(|| time.startup()) + TIME_OFFSET;
+
+    for (&fade, lifetime, start, mut opacity) in &mut particles {
+        *opacity = fade.opacity(current_time.saturating_duration_since(**start), **lifetime);
     }
 }
 
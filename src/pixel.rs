@@ -1,12 +1,21 @@
-pub(crate) trait Pixel: Copy + Default {
+//! Pixels, which images are made of
+
+/// A pixel that can be drawn to and from. Implemented for `u8`, a palette index that's always
+/// opaque, and `Option<u8>`, where `None` is transparent. Implement it to make custom canvases.
+pub trait Pixel: Copy + Default + Send + Sync {
+    /// Draws `pixel` onto this pixel. Transparent pixels leave this pixel unchanged.
     fn set(&mut self, pixel: impl Pixel) {
         if let Some(pixel) = pixel.get_value() {
             self.set_value(pixel);
         }
     }
 
+    /// The palette index of this pixel, or `None` if it's transparent
     fn get_value(&self) -> Option<u8>;
+    /// A mutable reference to the palette index of this pixel, or `None` if it's transparent
     fn get_value_mut(&mut self) -> Option<&mut u8>;
+    /// Sets this pixel to the given palette index. Afterward, [`Pixel::get_value`] must return
+    /// `Some(pixel)`.
     fn set_value(&mut self, pixel: u8);
 }
 
@@ -1,6 +1,9 @@
 //! Position, layers, velocity, anchors, etc.
 
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
 
 use bevy::{
     ecs::{component::ComponentId, world::DeferredWorld},
@@ -35,49 +38,68 @@ macro_rules! align_to_screen {
     }};
 }
 
-pub(crate) fn plug<L: PxLayer>(app: &mut App) {
-    app.insert_resource(InsertDefaultLayer::new::<L>())
-        .add_systems(
-            PreUpdate,
-            (
-                update_sub_positions,
+pub(crate) fn plug<L: PxLayer>(fixed_timestep_movement: bool) -> impl Fn(&mut App) {
+    move |app| {
+        let movement = (update_velocities, update_sub_positions)
+            .chain()
+            .run_if(resource_equals(PxTimePaused(false)));
+
+        match fixed_timestep_movement {
+            // `FixedUpdate` runs after `PreUpdate`, so sync `PxPosition` right after moving
+            // instead of waiting for the next frame's `PxSet::UpdatePosToSubPos`
+            true => app.add_systems(FixedUpdate, (movement, update_position_to_sub).chain()),
+            false => app.add_systems(PreUpdate, movement.before(PxSet::UpdatePosToSubPos)),
+        };
+
+        app.insert_resource(InsertDefaultLayer::new::<L>())
+            .add_systems(
+                PreUpdate,
                 update_position_to_sub.in_set(PxSet::UpdatePosToSubPos),
             )
-                .chain(),
-        )
-        .add_systems(
-            PostUpdate,
-            (
-                align_to_screen!(&PxMap, Res<Assets<PxTileset>>, |map: &PxMap,
-                                                                  tilesets: &Res<
-                    Assets<PxTileset>,
-                >| {
-                    Some((&map.tiles, tilesets.get(&map.tileset)?).frame_size())
-                }),
-                align_to_screen!(
-                    &PxSprite,
-                    Res<Assets<PxSpriteAsset>>,
-                    |sprite: &PxSprite, sprites: &Res<Assets<PxSpriteAsset>>| {
-                        Some(sprites.get(&**sprite)?.frame_size())
-                    }
-                ),
-                align_to_screen!(&PxRect, (), |rect: &PxRect, &()| Some(rect.frame_size())),
-                #[cfg(feature = "line")]
-                align_to_screen!(&PxLine, (), |line: &PxLine, &()| Some(line.frame_size())),
-            ),
-        )
-        .sub_app_mut(RenderApp)
-        .insert_resource(InsertDefaultLayer::new::<L>());
+            .add_systems(
+                PostUpdate,
+                (
+                    align_to_screen!(&PxMap, Res<Assets<PxTileset>>, |map: &PxMap,
+                                                                      tilesets: &Res<
+                        Assets<PxTileset>,
+                    >| {
+                        Some((&map.tiles, tilesets.get(&map.tileset)?).frame_size())
+                    }),
+                    align_to_screen!(
+                        &PxSprite,
+                        Res<Assets<PxSpriteAsset>>,
+                        |sprite: &PxSprite, sprites: &Res<Assets<PxSpriteAsset>>| {
+                            Some(sprites.get(&**sprite)?.frame_size())
+                        }
+                    ),
+                    align_to_screen!(&PxRect, (), |rect: &PxRect, &()| Some(rect.frame_size())),
+                    #[cfg(feature = "line")]
+                    align_to_screen!(&PxLine, (), |line: &PxLine, &()| Some(line.frame_size())),
+                )
+                    .run_if(resource_exists::<Screen>),
+            )
+            .sub_app_mut(RenderApp)
+            .insert_resource(InsertDefaultLayer::new::<L>());
+    }
 }
 
 pub(crate) trait Spatial {
     fn frame_size(&self) -> UVec2;
+
+    // Position of the anchor relative to the bottom-left of the drawn frame
+    fn anchor_pos(&self, anchor: PxAnchor) -> IVec2 {
+        anchor.pos(self.frame_size()).as_ivec2()
+    }
 }
 
 impl<T: Spatial> Spatial for &'_ T {
     fn frame_size(&self) -> UVec2 {
         (*self).frame_size()
     }
+
+    fn anchor_pos(&self, anchor: PxAnchor) -> IVec2 {
+        (*self).anchor_pos(anchor)
+    }
 }
 
 /// The position of an entity
@@ -90,6 +112,47 @@ impl From<IVec2> for PxPosition {
     }
 }
 
+impl From<(i32, i32)> for PxPosition {
+    fn from((x, y): (i32, i32)) -> Self {
+        Self(IVec2::new(x, y))
+    }
+}
+
+macro_rules! impl_position_ops {
+    ($position:ty, $vec:ty) => {
+        impl Add<$vec> for $position {
+            type Output = Self;
+
+            fn add(self, rhs: $vec) -> Self {
+                Self(self.0 + rhs)
+            }
+        }
+
+        impl Sub<$vec> for $position {
+            type Output = Self;
+
+            fn sub(self, rhs: $vec) -> Self {
+                Self(self.0 - rhs)
+            }
+        }
+
+        impl AddAssign<$vec> for $position {
+            fn add_assign(&mut self, rhs: $vec) {
+                self.0 += rhs;
+            }
+        }
+
+        impl SubAssign<$vec> for $position {
+            fn sub_assign(&mut self, rhs: $vec) {
+                self.0 -= rhs;
+            }
+        }
+    };
+}
+
+impl_position_ops!(PxPosition, IVec2);
+impl_position_ops!(PxSubPosition, Vec2);
+
 /// Trait implemented for your game's custom layer type. Use the [`px_layer`] attribute
 /// or derive/implement the required traits manually. The layers will be rendered in the order
 /// defined by the [`PartialOrd`] implementation. So, lower values will be in the back
@@ -99,10 +162,10 @@ pub trait PxLayer: ExtractComponent + Component + Ord + Clone + Default + Debug
 impl<L: ExtractComponent + Component + Ord + Clone + Default + Debug> PxLayer for L {}
 
 #[derive(Resource, Deref)]
-struct InsertDefaultLayer(Box<dyn Fn(&mut EntityWorldMut) + Send + Sync>);
+pub(crate) struct InsertDefaultLayer(Box<dyn Fn(&mut EntityWorldMut) + Send + Sync>);
 
 impl InsertDefaultLayer {
-    fn new<L: PxLayer>() -> Self {
+    pub(crate) fn new<L: PxLayer>() -> Self {
         Self(Box::new(|entity| {
             entity.insert_if_new(L::default());
         }))
@@ -180,6 +243,23 @@ impl PxAnchor {
     }
 }
 
+/// Offset, in pixels, applied after the [`PxAnchor`], such as to place a sprite 3 pixels above
+/// its bottom-center. Positive values move the entity up and to the right. Applies to sprites
+/// and to [`PxInteractBounds`](crate::button::PxInteractBounds).
+#[derive(Component, Deref, DerefMut, Clone, Copy, Default, Debug)]
+pub struct PxAnchorOffset(pub IVec2);
+
+impl From<IVec2> for PxAnchorOffset {
+    fn from(offset: IVec2) -> Self {
+        Self(offset)
+    }
+}
+
+/// Excludes an entity from rendering without changing its [`Visibility`] or other components.
+/// Hidden entities keep their layer, so they render as before once this is removed.
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct PxHidden;
+
 /// Aligns a spatial entity to a corner of the screen
 // TODO This is private because it's not done yet
 #[derive(Component)]
@@ -197,6 +277,12 @@ impl From<Vec2> for PxSubPosition {
     }
 }
 
+impl From<(f32, f32)> for PxSubPosition {
+    fn from((x, y): (f32, f32)) -> Self {
+        Self(Vec2::new(x, y))
+    }
+}
+
 #[cfg(feature = "nav")]
 impl Position2 for PxSubPosition {
     fn get(&self) -> Vec2 {
@@ -208,6 +294,57 @@ impl Position2 for PxSubPosition {
     }
 }
 
+/// How [`PxSnap`] converts a sub-pixel coordinate to a grid coordinate
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PxRoundMode {
+    /// Round to the nearest grid line
+    #[default]
+    Round,
+    /// Round down
+    Floor,
+    /// Round up
+    Ceil,
+}
+
+impl PxRoundMode {
+    fn apply(self, value: f32) -> f32 {
+        match self {
+            PxRoundMode::Round => value.round(),
+            PxRoundMode::Floor => value.floor(),
+            PxRoundMode::Ceil => value.ceil(),
+        }
+    }
+}
+
+/// Controls how [`PxSubPosition`] is converted to [`PxPosition`]. The position is snapped
+/// to multiples of `grid` on each axis, using `mode`. Without this component, or with
+/// the default, the sub-position is rounded to the nearest pixel.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+#[require(PxSubPosition)]
+pub struct PxSnap {
+    /// Size of the grid cells, in pixels. `0` is treated as `1`.
+    pub grid: UVec2,
+    /// How coordinates are rounded to the grid
+    pub mode: PxRoundMode,
+}
+
+impl Default for PxSnap {
+    fn default() -> Self {
+        Self {
+            grid: UVec2::ONE,
+            mode: default(),
+        }
+    }
+}
+
+impl PxSnap {
+    fn snap(self, position: Vec2) -> IVec2 {
+        let grid = self.grid.max(UVec2::ONE).as_vec2();
+        let cell = position / grid;
+        (Vec2::new(self.mode.apply(cell.x), self.mode.apply(cell.y)) * grid).as_ivec2()
+    }
+}
+
 /// Velocity. Entities with this and [`PxSubPosition`] will move at this velocity over time.
 #[derive(Clone, Component, Copy, Debug, Default, Deref, DerefMut)]
 #[require(PxSubPosition)]
@@ -219,9 +356,65 @@ impl From<Vec2> for PxVelocity {
     }
 }
 
-fn update_sub_positions(mut query: Query<(&mut PxSubPosition, &PxVelocity)>, time: Res<Time>) {
-    for (mut sub_position, velocity) in &mut query {
+impl PxVelocity {
+    /// Create a velocity moving at the given angle, in radians counterclockwise from the right,
+    /// and speed, in pixels per second
+    pub fn from_angle(radians: f32, speed: f32) -> Self {
+        Self(Vec2::from_angle(radians) * speed)
+    }
+
+    /// Create a velocity moving in the given direction, such as an [`Orthogonal`]
+    /// or [`Diagonal`], at the given speed, in pixels per second
+    pub fn from_direction(direction: impl Into<Dir2>, speed: f32) -> Self {
+        Self(*direction.into() * speed)
+    }
+
+    /// The angle of the velocity, in radians counterclockwise from the right.
+    /// Returns 0 if the velocity is zero.
+    pub fn angle(&self) -> f32 {
+        self.y.atan2(self.x)
+    }
+}
+
+/// Acceleration. Entities with this and [`PxVelocity`] will accelerate at this rate over time.
+#[derive(Clone, Component, Copy, Debug, Default, Deref, DerefMut)]
+#[require(PxVelocity)]
+pub struct PxAcceleration(pub Vec2);
+
+impl From<Vec2> for PxAcceleration {
+    fn from(vec: Vec2) -> Self {
+        Self(vec)
+    }
+}
+
+// Emitters pass their velocity and acceleration on to their particles, so they don't move
+#[cfg(feature = "particle")]
+type MovementFilter = Without<PxEmitter>;
+#[cfg(not(feature = "particle"))]
+type MovementFilter = ();
+
+fn update_velocities(
+    mut query: Query<(&mut PxVelocity, &PxAcceleration), MovementFilter>,
+    time: Res<Time>,
+) {
+    for (mut velocity, acceleration) in &mut query {
+        if **acceleration != Vec2::ZERO {
+            **velocity += **acceleration * time.delta_secs();
+        }
+    }
+}
+
+fn update_sub_positions(
+    mut query: Query<(&mut PxSubPosition, &PxVelocity, Has<PxSnap>), MovementFilter>,
+    time: Res<Time>,
+) {
+    for (mut sub_position, velocity, snapped) in &mut query {
         if **velocity == Vec2::ZERO {
+            // Rounding here would override `PxSnap`'s mode
+            if snapped {
+                continue;
+            }
+
             let new_position = Vec2::new(sub_position.x.round(), sub_position.y.round());
             if **sub_position != new_position {
                 **sub_position = new_position;
@@ -233,10 +426,13 @@ fn update_sub_positions(mut query: Query<(&mut PxSubPosition, &PxVelocity)>, tim
 }
 
 fn update_position_to_sub(
-    mut query: Query<(&mut PxPosition, &PxSubPosition), Changed<PxSubPosition>>,
+    mut query: Query<
+        (&mut PxPosition, &PxSubPosition, Option<&PxSnap>),
+        Or<(Changed<PxSubPosition>, Changed<PxSnap>)>,
+    >,
 ) {
-    for (mut position, sub_position) in &mut query {
-        let new_position = IVec2::new(sub_position.x.round() as i32, sub_position.y.round() as i32);
+    for (mut position, sub_position, snap) in &mut query {
+        let new_position = snap.copied().unwrap_or_default().snap(**sub_position);
         if **position != new_position {
             **position = new_position;
         }
@@ -8,26 +8,52 @@ pub(crate) use seldom_map_nav::prelude::*;
 #[cfg(feature = "state")]
 pub(crate) use seldom_state::prelude::*;
 
+#[cfg(feature = "state")]
+pub use crate::animation::PxAnimationOnState;
+#[cfg(feature = "line")]
+pub use crate::gizmos::PxGizmos;
 #[cfg(feature = "line")]
-pub use crate::line::PxLine;
+pub use crate::line::{
+    PxCircle, PxLine, PxLineArrow, PxLineDash, PxLineWidth, PxPolygon, PxStroke, PxSubLine,
+};
+#[cfg(feature = "tiled")]
+pub use crate::map::PxTiledMap;
 #[cfg(feature = "particle")]
-pub use crate::particle::{PxEmitter, PxEmitterFrequency, PxEmitterSimulation, PxParticleLifetime};
+pub use crate::particle::{
+    PxEmitter, PxEmitterFrequency, PxEmitterMode, PxEmitterShape, PxEmitterSimulation,
+    PxEmitterVelocity, PxParticleEmitter, PxParticleFade, PxParticleLifetime,
+};
+#[cfg(feature = "stats_overlay")]
+pub use crate::stats::PxStatsOverlay;
 pub use crate::{
     animation::{
         PxAnimation, PxAnimationDirection, PxAnimationDuration, PxAnimationFinishBehavior,
-        PxAnimationFinished, PxAnimationFrameTransition,
+        PxAnimationFinished, PxAnimationFrameTransition, PxTimePaused,
     },
     button::{PxButtonFilter, PxButtonSprite, PxClick, PxEnableButtons, PxHover, PxInteractBounds},
-    camera::{PxCamera, PxCanvas},
-    cursor::PxCursor,
-    filter::{PxFilter, PxFilterAsset, PxFilterLayers},
-    map::{PxMap, PxTile, PxTiles, PxTileset},
+    camera::{
+        PxCamera, PxCameraBounds, PxCameraFollow, PxCameraShake, PxCanvas, PxParallax,
+        PxViewportCamera,
+    },
+    cursor::{PxCursor, PxCursorWorldPosition, PxGamepadCursor},
+    dither::{PxDither, PxDitherAlgorithm},
+    drawable::{PxDrawable, PxDrawableAppExt},
+    filter::{PxFilter, PxFilterAsset, PxFilterLayers, PxRuntimeFilter},
+    map::{PxMap, PxMapData, PxTile, PxTiles, PxTileset},
     math::{Diagonal, Orthogonal},
-    position::{PxAnchor, PxLayer, PxPosition, PxSubPosition, PxVelocity},
-    screen::ScreenSize,
-    sprite::{PxSprite, PxSpriteAsset},
+    position::{
+        PxAcceleration, PxAnchor, PxAnchorOffset, PxHidden, PxLayer, PxPosition, PxRoundMode,
+        PxSnap, PxSubPosition, PxVelocity,
+    },
+    screen::{
+        PxBackgroundColor, PxImageTarget, PxLetterboxColor, PxPaletteSwatch, ScreenScaling,
+        ScreenSize,
+    },
+    screenshot::{PxScreenshot, PxScreenshotTaken},
+    sprite::{PxOpacity, PxRotation, PxSprite, PxSpriteAsset, PxSpriteOutline, PxVisibilityRange},
+    stats::{PxLayerStats, PxRenderStats},
     text::{PxText, PxTypeface},
-    ui::PxRect,
+    ui::{PxRect, PxRectFollow},
     PxPlugin,
 };
 pub use seldom_pixel_macros::px_layer;
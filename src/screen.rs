@@ -1,13 +1,14 @@
 //! Screen and rendering
 
-use std::{collections::BTreeMap, marker::PhantomData};
+use std::{collections::BTreeMap, marker::PhantomData, mem, sync::Mutex};
 
 use bevy::{
     core_pipeline::core_2d::graph::{Core2d, Node2d},
-    image::TextureFormatPixelInfo,
+    image::{ImageSampler, TextureFormatPixelInfo},
     render::{
+        camera::ExtractedCamera,
         extract_resource::{ExtractResource, ExtractResourcePlugin},
-        render_asset::RenderAssets,
+        render_asset::{RenderAsset, RenderAssetUsages, RenderAssets},
         render_graph::{
             NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
         },
@@ -15,30 +16,37 @@ use bevy::{
             binding_types::{texture_2d, uniform_buffer},
             BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId,
             ColorTargetState, ColorWrites, DynamicUniformBuffer, Extent3d, FragmentState,
-            ImageDataLayout, PipelineCache, RenderPassColorAttachment, RenderPassDescriptor,
-            RenderPipelineDescriptor, ShaderStages, ShaderType, TextureDimension, TextureFormat,
-            TextureSampleType, TextureViewDescriptor, TextureViewDimension, VertexState,
+            ImageDataLayout, LoadOp, Operations, PipelineCache, RenderPassColorAttachment,
+            RenderPassDescriptor, RenderPipelineDescriptor, ShaderStages, ShaderType, StoreOp,
+            TextureDimension, TextureFormat, TextureSampleType, TextureUsages, TextureView,
+            TextureViewDescriptor, TextureViewDimension, VertexState,
         },
         renderer::{RenderContext, RenderDevice, RenderQueue},
+        texture::GpuImage,
         view::ViewTarget,
-        Render, RenderApp, RenderSet,
+        Extract, Render, RenderApp, RenderSet,
     },
+    utils::Instant,
     window::{PrimaryWindow, WindowResized},
 };
 
 #[cfg(feature = "line")]
 use crate::line::{draw_line, LineComponents};
 use crate::{
-    animation::{copy_animation_params, draw_spatial, LastUpdate},
+    animation::{copy_animation_params, draw_spatial, spatial_rect, LastUpdate},
+    camera::{CameraShakeOffset, PxViewportCamera},
     cursor::{CursorState, PxCursorPosition},
+    drawable::ExtractedDrawables,
     filter::{draw_filter, FilterComponents},
     image::{PxImage, PxImageSliceMut},
     map::{MapComponents, PxTile, TileComponents},
     math::RectExt,
-    palette::{PaletteHandle, PaletteParam},
-    position::PxLayer,
+    palette::{Palette, PaletteHandle, PaletteParam, PxLayerPalette, PxPaletteTransition},
+    position::{PxLayer, Spatial},
     prelude::*,
-    sprite::SpriteComponents,
+    screenshot::ScreenshotState,
+    sprite::{draw_outline, RotatedSprite, SpriteComponents},
+    stats::RenderStatsState,
     text::TextComponents,
 };
 
@@ -61,16 +69,41 @@ impl<L: PxLayer> Plug<L> {
 
 impl<L: PxLayer> Plugin for Plug<L> {
     fn build(&self, app: &mut App) {
-        app.add_plugins(ExtractResourcePlugin::<Screen>::default())
-            .add_systems(Startup, insert_screen(self.size))
-            .add_systems(Update, init_screen)
-            .add_systems(PostUpdate, (resize_screen, update_screen_palette))
-            .world_mut()
-            .resource_mut::<Assets<Shader>>()
-            .insert(
-                SCREEN_SHADER_HANDLE.id(),
-                Shader::from_wgsl(include_str!("screen.wgsl"), "screen.wgsl"),
-            );
+        app.add_plugins((
+            ExtractResourcePlugin::<Screen>::default(),
+            ExtractResourcePlugin::<PxBackgroundColor>::default(),
+            ExtractResourcePlugin::<PxLetterboxColor>::default(),
+            ExtractResourcePlugin::<PxImageTarget>::default(),
+        ))
+        .init_resource::<PxBackgroundColor>()
+        .init_resource::<PxLetterboxColor>()
+        .add_systems(Startup, insert_screen(self.size))
+        // In case the window didn't exist yet during `Startup`
+        .add_systems(
+            First,
+            insert_screen(self.size).run_if(not(resource_exists::<Screen>)),
+        )
+        .add_systems(Update, init_screen.run_if(resource_exists::<Screen>))
+        .add_systems(
+            PostUpdate,
+            (
+                resize_screen,
+                (
+                    update_screen_palette.run_if(resource_exists::<PaletteHandle>),
+                    update_palette_transition.run_if(resource_exists::<PxPaletteTransition>),
+                    update_palette_swatch.run_if(resource_exists::<PxPaletteSwatch>),
+                )
+                    .chain(),
+                update_layer_palette::<L>,
+            )
+                .run_if(resource_exists::<Screen>),
+        )
+        .world_mut()
+        .resource_mut::<Assets<Shader>>()
+        .insert(
+            SCREEN_SHADER_HANDLE.id(),
+            Shader::from_wgsl(include_str!("screen.wgsl"), "screen.wgsl"),
+        );
 
         app.sub_app_mut(RenderApp)
             .add_render_graph_node::<ViewNodeRunner<PxRenderNode<L>>>(Core2d, PxRender)
@@ -83,7 +116,14 @@ impl<L: PxLayer> Plugin for Plug<L> {
                 ),
             )
             .init_resource::<PxUniformBuffer>()
-            .add_systems(Render, prepare_uniform.in_set(RenderSet::Prepare));
+            .init_resource::<CachedComposeBuffers>()
+            .add_systems(ExtractSchedule, extract_layer_palettes::<L>)
+            .add_systems(
+                Render,
+                prepare_uniform
+                    .run_if(resource_exists::<Screen>)
+                    .in_set(RenderSet::Prepare),
+            );
     }
 
     fn finish(&self, app: &mut App) {
@@ -125,13 +165,27 @@ impl ScreenSize {
     }
 }
 
+/// How the screen is scaled up to fit the window
+#[derive(Clone, Copy, Default, Debug)]
+pub enum ScreenScaling {
+    /// The screen is scaled to fill as much of the window as possible. Pixels may be scaled
+    /// by a fractional amount, so some may appear larger than others.
+    #[default]
+    Fit,
+    /// The screen is scaled by the largest integer that fits in the window, and centered
+    IntegerOnly,
+}
+
 /// Metadata for the image that `seldom_pixel` draws to
 #[derive(ExtractResource, Resource, Clone, Debug)]
 pub struct Screen {
     pub(crate) size: ScreenSize,
     pub(crate) computed_size: UVec2,
     window_aspect_ratio: f32,
+    window_size: UVec2,
+    scaling: ScreenScaling,
     pub(crate) palette: [Vec3; 256],
+    layer_palette: Option<(AssetId<Palette>, [Vec3; 256])>,
     // pub(crate) palette_tree: ImmutableKdTree<f32, 3>,
 }
 
@@ -140,26 +194,151 @@ impl Screen {
     pub fn size(&self) -> UVec2 {
         self.computed_size
     }
+
+    /// Change the size of the screen. Takes effect on the next frame.
+    pub fn set_size(&mut self, size: impl Into<ScreenSize>) {
+        self.size = size.into();
+        // Only the window's aspect ratio matters here
+        self.computed_size = self.size.compute(Vec2::new(self.window_aspect_ratio, 1.));
+    }
+
+    /// Change how the screen is scaled up to fit the window
+    pub fn set_scaling(&mut self, scaling: ScreenScaling) {
+        self.scaling = scaling;
+    }
+
+    /// Converts a position in the window, in logical pixels from the top-left, like
+    /// [`Window::cursor_position`], to a position on the screen, in pixels from the bottom-left.
+    /// Returns `None` if the position is outside the screen.
+    pub fn window_to_screen(&self, window: &Window, position: Vec2) -> Option<UVec2> {
+        let window_size = Vec2::new(window.width(), window.height());
+        let screen_size = self.computed_size.as_vec2();
+        let position = Vec2::new(position.x, window_size.y - position.y) - window_size / 2.;
+        let position =
+            position / (self.fit_factor() * window_size) * screen_size + screen_size / 2.;
+
+        (position.cmpge(Vec2::ZERO).all() && position.cmplt(screen_size).all())
+            .then(|| position.as_uvec2())
+    }
+
+    /// Fraction of the window that the screen covers on each axis
+    pub(crate) fn fit_factor(&self) -> Vec2 {
+        match self.scaling {
+            ScreenScaling::Fit => {
+                let aspect_ratio_ratio = self.computed_size.x as f32
+                    / self.computed_size.y as f32
+                    / self.window_aspect_ratio;
+
+                if aspect_ratio_ratio > 1. {
+                    Vec2::new(1., 1. / aspect_ratio_ratio)
+                } else {
+                    Vec2::new(aspect_ratio_ratio, 1.)
+                }
+            }
+            ScreenScaling::IntegerOnly => {
+                let window_size = self.window_size.as_vec2();
+                let screen_size = self.computed_size.as_vec2();
+                let scale = (window_size / screen_size).min_element().floor().max(1.);
+
+                screen_size * scale / window_size
+            }
+        }
+    }
 }
 
-pub(crate) fn screen_scale(screen_size: UVec2, window_size: Vec2) -> Vec2 {
-    let aspect = screen_size.y as f32 / screen_size.x as f32;
+/// Resource containing the palette index that the screen is cleared to. Defaults to 0,
+/// the top-left pixel in the palette.
+#[derive(ExtractResource, Resource, Deref, DerefMut, Clone, Copy, Default, Debug)]
+pub struct PxBackgroundColor(pub u8);
 
-    Vec2::from(match window_size.y > aspect * window_size.x {
-        true => (window_size.x, window_size.x * aspect),
-        false => (window_size.y / aspect, window_size.y),
-    })
+/// Resource containing the color of the bars around the screen when the window's aspect ratio
+/// doesn't match the screen's. Defaults to black.
+#[derive(ExtractResource, Resource, Deref, DerefMut, Clone, Copy, Debug)]
+pub struct PxLetterboxColor(pub Color);
+
+impl Default for PxLetterboxColor {
+    fn default() -> Self {
+        Self(Color::BLACK)
+    }
+}
+
+/// Resource that makes `seldom_pixel` draw the screen into an image instead of the window,
+/// so it can be used in UI or on a mesh. The screen is stretched to fill the image.
+/// Create a compatible image with [`PxImageTarget::image`].
+#[derive(ExtractResource, Resource, Deref, DerefMut, Clone, Debug)]
+pub struct PxImageTarget(pub Handle<Image>);
+
+impl PxImageTarget {
+    /// Create an image that can be used as a [`PxImageTarget`]. Use the screen's size,
+    /// or a multiple of it, to keep pixels square.
+    pub fn image(size: UVec2) -> Image {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0; 4],
+            TextureFormat::bevy_default(),
+            RenderAssetUsages::all(),
+        );
+
+        image.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+        image
+    }
+}
+
+/// Resource that makes `seldom_pixel` copy the game's current palette into an image
+/// whenever it changes, as a 16x16 grid with one pixel per palette index, starting
+/// from the top-left. Display the image with a Bevy `Sprite` or `ImageNode` to debug
+/// palette changes and transitions.
+#[derive(Resource, Deref, DerefMut, Clone, Debug)]
+pub struct PxPaletteSwatch(pub Handle<Image>);
+
+fn update_palette_swatch(
+    swatch: Res<PxPaletteSwatch>,
+    screen: Res<Screen>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !swatch.is_changed() && !screen.is_changed() {
+        return;
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: 16,
+            height: 16,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        screen
+            .palette
+            .iter()
+            .flat_map(|color| Srgba::from(LinearRgba::rgb(color.x, color.y, color.z)).to_u8_array())
+            .collect(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    );
+
+    image.sampler = ImageSampler::nearest();
+    images.insert(&**swatch, image);
 }
 
 fn insert_screen(size: ScreenSize) -> impl Fn(Query<&Window, With<PrimaryWindow>>, Commands) {
     move |windows, mut commands| {
-        let window = windows.single();
+        let Ok(window) = windows.get_single() else {
+            return;
+        };
 
         commands.insert_resource(Screen {
             size,
             computed_size: size.compute(Vec2::new(window.width(), window.height())),
             window_aspect_ratio: window.width() / window.height(),
+            window_size: window.physical_size(),
+            scaling: default(),
             palette: [Vec3::ZERO; 256],
+            layer_palette: None,
             // palette_tree: ImmutableKdTree::from(&[][..]),
         });
     }
@@ -185,18 +364,92 @@ fn init_screen(mut initialized: Local<bool>, palette: PaletteParam, mut screen:
     *initialized = false;
 }
 
-fn resize_screen(mut window_resized: EventReader<WindowResized>, mut screen: ResMut<Screen>) {
+fn resize_screen(
+    mut window_resized: EventReader<WindowResized>,
+    windows: Query<&Window>,
+    mut screen: ResMut<Screen>,
+) {
     if let Some(window_resized) = window_resized.read().last() {
         screen.computed_size = screen
             .size
             .compute(Vec2::new(window_resized.width, window_resized.height));
         screen.window_aspect_ratio = window_resized.width / window_resized.height;
+
+        if let Ok(window) = windows.get(window_resized.window) {
+            screen.window_size = window.physical_size();
+        }
     }
 }
 
+fn update_layer_palette<L: PxLayer>(
+    mut warned: Local<bool>,
+    layer_palettes: Query<&PxLayerPalette<L>>,
+    palettes: Res<Assets<Palette>>,
+    mut screen: ResMut<Screen>,
+) {
+    // Only one alternate palette fits in the uniform, so use the lowest layer's
+    let layer_palette = layer_palettes
+        .iter()
+        .min_by(|left, right| left.layer.cmp(&right.layer));
+
+    let conflicting = layer_palette.is_some_and(|layer_palette| {
+        layer_palettes
+            .iter()
+            .any(|other| other.palette.id() != layer_palette.palette.id())
+    });
+
+    if conflicting && !*warned {
+        warn!(
+            "multiple alternate palettes are in use by `PxLayerPalette`s, but only one can be \
+            displayed at a time; layers with a palette other than {:?}'s are drawn with the game's \
+            palette",
+            layer_palette.map(|layer_palette| &layer_palette.layer),
+        );
+    }
+    *warned = conflicting;
+
+    let layer_palette = layer_palette.and_then(|layer_palette| {
+        let palette = palettes.get(&layer_palette.palette)?;
+        let mut screen_palette = [Vec3::ZERO; 256];
+
+        for (i, [r, g, b]) in palette.colors.iter().enumerate() {
+            screen_palette[i] = Color::srgb_u8(*r, *g, *b).to_linear().to_vec3();
+        }
+
+        Some((layer_palette.palette.id(), screen_palette))
+    });
+
+    if screen.layer_palette != layer_palette {
+        screen.layer_palette = layer_palette;
+    }
+}
+
+#[derive(Resource, Deref)]
+struct LayerPalettes<L: PxLayer>(Vec<L>);
+
+fn extract_layer_palettes<L: PxLayer>(
+    layer_palettes: Extract<Query<&PxLayerPalette<L>>>,
+    screen: Extract<Option<Res<Screen>>>,
+    mut commands: Commands,
+) {
+    let id = screen
+        .as_ref()
+        .and_then(|screen| screen.layer_palette)
+        .map(|(id, _)| id);
+
+    commands.insert_resource(LayerPalettes(
+        layer_palettes
+            .iter()
+            .filter(|layer_palette| Some(layer_palette.palette.id()) == id)
+            .map(|layer_palette| layer_palette.layer.clone())
+            .collect(),
+    ));
+}
+
 #[derive(ShaderType)]
 struct PxUniform {
-    palette: [Vec3; 256],
+    // The game's palette, then the alternate palette for layers with a `PxLayerPalette`
+    palettes: [[Vec3; 256]; 2],
     fit_factor: Vec2,
 }
 
@@ -206,6 +459,7 @@ struct PxUniformBuffer(DynamicUniformBuffer<PxUniform>);
 fn prepare_uniform(
     mut buffer: ResMut<PxUniformBuffer>,
     screen: Res<Screen>,
+    image_target: Option<Res<PxImageTarget>>,
     device: Res<RenderDevice>,
     queue: Res<RenderQueue>,
 ) {
@@ -213,14 +467,16 @@ fn prepare_uniform(
         return;
     };
 
-    let aspect_ratio_ratio =
-        screen.computed_size.x as f32 / screen.computed_size.y as f32 / screen.window_aspect_ratio;
     writer.write(&PxUniform {
-        palette: screen.palette,
-        fit_factor: if aspect_ratio_ratio > 1. {
-            Vec2::new(1., 1. / aspect_ratio_ratio)
-        } else {
-            Vec2::new(aspect_ratio_ratio, 1.)
+        palettes: [
+            screen.palette,
+            screen
+                .layer_palette
+                .map_or(screen.palette, |(_, palette)| palette),
+        ],
+        fit_factor: match image_target {
+            Some(_) => Vec2::ONE,
+            None => screen.fit_factor(),
         },
     });
 }
@@ -242,6 +498,7 @@ impl FromWorld for PxPipeline {
                 (
                     texture_2d(TextureSampleType::Uint),
                     uniform_buffer::<PxUniform>(false).visibility(ShaderStages::VERTEX_FRAGMENT),
+                    texture_2d(TextureSampleType::Uint),
                 ),
             ),
         );
@@ -279,71 +536,242 @@ impl FromWorld for PxPipeline {
     }
 }
 
+/// Composes the screen on the CPU, without the render graph. Returns an `R8Uint` image
+/// of palette indices, or `None` if there is no [`Screen`]. Draws from the main world,
+/// so visibility, layer palettes, custom [`PxDrawable`]s, and the in-game cursor
+/// are not taken into account.
+/// Useful for deterministic snapshot tests and generating thumbnails on a server.
+pub fn compose_screen<L: PxLayer>(world: &mut World) -> Option<Image> {
+    let last_update = world
+        .get_resource::<Time<Real>>()
+        .and_then(|time| time.last_update())
+        .unwrap_or_else(Instant::now);
+    let node = PxRenderNode::<L>::from_world(world);
+    let mut buffers = default();
+
+    node.compose(
+        world,
+        last_update,
+        PxCamera(**world.resource::<PxCamera>() + **world.resource::<CameraShakeOffset>()),
+        None,
+        &[],
+        world.resource::<Assets<PxTileset>>(),
+        world.resource::<Assets<PxSpriteAsset>>(),
+        world.resource::<Assets<PxTypeface>>(),
+        world.resource::<Assets<PxFilterAsset>>(),
+        &mut buffers,
+    )?;
+
+    Some(buffers.image)
+}
+
+/// Gets the palette index drawn at the given world position on the given layer, or `None`
+/// if nothing opaque is drawn there, such as to check whether the player is behind a wall.
+/// Includes everything drawn on the layer, such as maps and text, and filters that clip
+/// to it, but not entities on other layers. Composes the layer on the CPU from the main world,
+/// with the same caveats as [`compose_screen`], so only positions on the screen can be hit.
+/// This composes the whole layer, so batch queries where possible.
+pub fn px_hit_test<L: PxLayer>(world: &mut World, position: IVec2, layer: &L) -> Option<u8> {
+    let last_update = world
+        .get_resource::<Time<Real>>()
+        .and_then(|time| time.last_update())
+        .unwrap_or_else(Instant::now);
+    let node = PxRenderNode::<L>::from_world(world);
+    let mut buffers = ComposeBuffers::default();
+    let camera = PxCamera(**world.resource::<PxCamera>() + **world.resource::<CameraShakeOffset>());
+
+    node.compose(
+        world,
+        last_update,
+        camera,
+        Some(layer),
+        &[],
+        world.resource::<Assets<PxTileset>>(),
+        world.resource::<Assets<PxSpriteAsset>>(),
+        world.resource::<Assets<PxTypeface>>(),
+        world.resource::<Assets<PxFilterAsset>>(),
+        &mut buffers,
+    )?;
+
+    let position = camera.world_to_screen(position, PxCanvas::World);
+    let layer_image = &buffers.layer_image;
+
+    layer_image
+        .get_pixel(IVec2::new(
+            position.x,
+            layer_image.height() as i32 - 1 - position.y,
+        ))
+        .flatten()
+}
+
+/// Asserts that an image composed with [`compose_screen`] matches a golden image. The golden
+/// image is a list of rows of palette indices, from top to bottom. On a mismatch, reports
+/// the first differing pixel, with `y` counted from the bottom, like [`PxPosition`].
+#[macro_export]
+macro_rules! assert_px_image_eq {
+    ($image:expr, $golden:expr $(,)?) => {{
+        let image = &$image;
+        let golden = &$golden;
+        let width = image.width() as usize;
+        let height = image.height() as usize;
+
+        assert_eq!(
+            golden.len(),
+            height,
+            "image height is {height}, but the golden image's height is {}",
+            golden.len(),
+        );
+
+        for (row, golden_row) in golden.iter().enumerate() {
+            assert_eq!(
+                golden_row.len(),
+                width,
+                "image width is {width}, but row {row} of the golden image has width {}",
+                golden_row.len(),
+            );
+
+            for (x, &expected) in golden_row.iter().enumerate() {
+                let actual = image.data[row * width + x];
+                assert!(
+                    actual == expected,
+                    "images differ at ({x}, {}): expected {expected}, found {actual}",
+                    height - 1 - row,
+                );
+            }
+        }
+    }};
+}
+
+/// Asset storage that the layers can be composed from. Implemented for both
+/// main world and render world assets.
+trait AssetLookup<A: Asset> {
+    fn get(&self, id: impl Into<AssetId<A>>) -> Option<&A>;
+}
+
+impl<A: Asset> AssetLookup<A> for Assets<A> {
+    fn get(&self, id: impl Into<AssetId<A>>) -> Option<&A> {
+        Assets::get(self, id)
+    }
+}
+
+impl<A: Asset + RenderAsset<SourceAsset = A>> AssetLookup<A> for RenderAssets<A> {
+    fn get(&self, id: impl Into<AssetId<A>>) -> Option<&A> {
+        RenderAssets::get(self, id)
+    }
+}
+
+/// Buffers that compositing draws into. They're reused across frames,
+/// and only reallocated when the screen is resized.
+struct ComposeBuffers {
+    image: Image,
+    layer_image: PxImage<Option<u8>>,
+    silhouette_image: PxImage<Option<u8>>,
+    // Translucent sprites are drawn here before they're dithered
+    faded_image: PxImage<Option<u8>>,
+    // 1 where the pixel uses the layer palette, and 0 where it uses the game's palette
+    palette_mask: Vec<u8>,
+}
+
+impl Default for ComposeBuffers {
+    fn default() -> Self {
+        Self {
+            image: default(),
+            layer_image: PxImage::empty(UVec2::ZERO),
+            silhouette_image: PxImage::empty(UVec2::ZERO),
+            faded_image: PxImage::empty(UVec2::ZERO),
+            palette_mask: Vec::new(),
+        }
+    }
+}
+
+impl ComposeBuffers {
+    fn prepare(&mut self, size: UVec2, background: u8) {
+        if self.image.size() == size
+            && self.image.texture_descriptor.format == TextureFormat::R8Uint
+        {
+            self.image.data.fill(background);
+            self.palette_mask.fill(0);
+            return;
+        }
+
+        self.image = Image::new_fill(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[background],
+            TextureFormat::R8Uint,
+            default(),
+        );
+        self.layer_image = PxImage::empty(size);
+        self.silhouette_image = PxImage::empty(size);
+        self.palette_mask = vec![0; self.layer_image.area()];
+    }
+}
+
+#[derive(Resource, Deref, Default)]
+struct CachedComposeBuffers(Mutex<ComposeBuffers>);
+
 #[derive(RenderLabel, Hash, Eq, PartialEq, Clone, Debug)]
 struct PxRender;
 
 struct PxRenderNode<L: PxLayer> {
-    maps: QueryState<MapComponents<L>>,
-    tiles: QueryState<TileComponents>,
+    maps: QueryState<MapComponents<L>, Without<PxHidden>>,
+    tiles: QueryState<TileComponents, Without<PxHidden>>,
     // image_to_sprites: QueryState<ImageToSpriteComponents<L>>,
-    sprites: QueryState<SpriteComponents<L>>,
-    texts: QueryState<TextComponents<L>>,
+    sprites: QueryState<SpriteComponents<L>, Without<PxHidden>>,
+    texts: QueryState<TextComponents<L>, Without<PxHidden>>,
     #[cfg(feature = "line")]
-    lines: QueryState<LineComponents<L>>,
-    filters: QueryState<FilterComponents<L>, Without<PxCanvas>>,
+    lines: QueryState<LineComponents<L>, Without<PxHidden>>,
+    filters: QueryState<FilterComponents<L>, (Without<PxCanvas>, Without<PxHidden>)>,
 }
 
 impl<L: PxLayer> FromWorld for PxRenderNode<L> {
     fn from_world(world: &mut World) -> Self {
         Self {
-            maps: world.query(),
-            tiles: world.query(),
+            maps: world.query_filtered(),
+            tiles: world.query_filtered(),
             // image_to_sprites: world.query(),
-            sprites: world.query(),
-            texts: world.query(),
+            sprites: world.query_filtered(),
+            texts: world.query_filtered(),
             #[cfg(feature = "line")]
-            lines: world.query(),
+            lines: world.query_filtered(),
             filters: world.query_filtered(),
         }
     }
 }
 
-impl<L: PxLayer> ViewNode for PxRenderNode<L> {
-    type ViewQuery = &'static ViewTarget;
-
-    fn update(&mut self, world: &mut World) {
-        self.maps.update_archetypes(world);
-        self.tiles.update_archetypes(world);
-        // self.image_to_sprites.update_archetypes(world);
-        self.sprites.update_archetypes(world);
-        self.texts.update_archetypes(world);
-        #[cfg(feature = "line")]
-        self.lines.update_archetypes(world);
-        self.filters.update_archetypes(world);
-    }
-
-    fn run<'w>(
+impl<L: PxLayer> PxRenderNode<L> {
+    /// Composes the layers on the CPU. Returns the `R8Uint` image of palette indices
+    /// and the palette mask.
+    fn compose(
         &self,
-        _: &mut RenderGraphContext,
-        render_context: &mut RenderContext<'w>,
-        target: &ViewTarget,
-        world: &'w World,
-    ) -> Result<(), NodeRunError> {
-        let &camera = world.resource::<PxCamera>();
-        let &LastUpdate(last_update) = world.resource::<LastUpdate>();
-        let screen = world.resource::<Screen>();
-
-        let mut image = Image::new_fill(
-            Extent3d {
-                width: screen.computed_size.x,
-                height: screen.computed_size.y,
-                depth_or_array_layers: 1,
-            },
-            TextureDimension::D2,
-            &[0],
-            TextureFormat::R8Uint,
-            default(),
+        world: &World,
+        last_update: Instant,
+        camera: PxCamera,
+        only_layer: Option<&L>,
+        layer_palettes: &[L],
+        tilesets: &impl AssetLookup<PxTileset>,
+        sprite_assets: &impl AssetLookup<PxSpriteAsset>,
+        typefaces: &impl AssetLookup<PxTypeface>,
+        filters: &impl AssetLookup<PxFilterAsset>,
+        buffers: &mut ComposeBuffers,
+    ) -> Option<BTreeMap<L, PxLayerStats>> {
+        let screen = world.get_resource::<Screen>()?;
+
+        buffers.prepare(
+            screen.computed_size,
+            **world.resource::<PxBackgroundColor>(),
         );
+        let ComposeBuffers {
+            image,
+            layer_image,
+            silhouette_image,
+            faded_image,
+            palette_mask,
+        } = buffers;
 
         #[cfg(feature = "line")]
         let mut layer_contents =
@@ -352,14 +780,16 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
         let mut layer_contents =
             BTreeMap::<_, (Vec<_>, Vec<_>, Vec<_>, (), Vec<_>, (), Vec<_>)>::default();
 
-        for (map, position, layer, canvas, animation, filter) in self.maps.iter_manual(world) {
+        for (map, position, layer, canvas, animation, filter, parallax) in
+            self.maps.iter_manual(world)
+        {
             if let Some((maps, _, _, _, _, _, _)) = layer_contents.get_mut(layer) {
-                maps.push((map, position, canvas, animation, filter));
+                maps.push((map, position, canvas, animation, filter, parallax));
             } else {
                 layer_contents.insert(
                     layer.clone(),
                     (
-                        vec![(map, position, canvas, animation, filter)],
+                        vec![(map, position, canvas, animation, filter, parallax)],
                         // default(),
                         default(),
                         default(),
@@ -394,17 +824,53 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
         //     }
         // }
 
-        for (sprite, position, anchor, layer, canvas, animation, filter) in
-            self.sprites.iter_manual(world)
+        for (
+            sprite,
+            position,
+            anchor,
+            layer,
+            canvas,
+            animation,
+            filter,
+            opacity,
+            parallax,
+            rotation,
+            outline,
+            anchor_offset,
+        ) in self.sprites.iter_manual(world)
         {
             if let Some((_, sprites, _, _, _, _, _)) = layer_contents.get_mut(layer) {
-                sprites.push((sprite, position, anchor, canvas, animation, filter));
+                sprites.push((
+                    sprite,
+                    position,
+                    anchor,
+                    canvas,
+                    animation,
+                    filter,
+                    opacity,
+                    parallax,
+                    rotation,
+                    outline,
+                    anchor_offset,
+                ));
             } else {
                 layer_contents.insert(
                     layer.clone(),
                     (
                         default(),
-                        vec![(sprite, position, anchor, canvas, animation, filter)],
+                        vec![(
+                            sprite,
+                            position,
+                            anchor,
+                            canvas,
+                            animation,
+                            filter,
+                            opacity,
+                            parallax,
+                            rotation,
+                            outline,
+                            anchor_offset,
+                        )],
                         default(),
                         default(),
                         default(),
@@ -437,7 +903,7 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
         }
 
         #[cfg(feature = "line")]
-        for (line, filter, layers, canvas, animation) in self.lines.iter_manual(world) {
+        for (line, filter, layers, canvas, animation, style) in self.lines.iter_manual(world) {
             for (layer, clip) in match layers {
                 PxFilterLayers::Single { layer, clip } => vec![(layer.clone(), *clip)],
                 PxFilterLayers::Many(layers) => {
@@ -455,9 +921,9 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
                     layer_contents.get_mut(&layer)
                 {
                     if clip { clip_lines } else { over_lines }
-                        .push((line, filter, canvas, animation));
+                        .push((line, filter, canvas, animation, style));
                 } else {
-                    let lines = vec![(line, filter, canvas, animation)];
+                    let lines = vec![(line, filter, canvas, animation, style)];
 
                     layer_contents.insert(
                         layer,
@@ -536,18 +1002,22 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
             }
         }
 
-        let tilesets = world.resource::<RenderAssets<PxTileset>>();
-        // let images = world.resource::<RenderAssets<GpuImage>>();
-        let sprite_assets = world.resource::<RenderAssets<PxSpriteAsset>>();
-        let typefaces = world.resource::<RenderAssets<PxTypeface>>();
-        let filters = world.resource::<RenderAssets<PxFilterAsset>>();
+        let mut drawables = BTreeMap::<_, Vec<_>>::new();
 
-        let mut layer_image = PxImage::<Option<u8>>::empty_from_image(&image);
-        let mut image_slice = PxImageSliceMut::from_image_mut(&mut image);
+        if let Some(extracted) = world.get_resource::<ExtractedDrawables<L>>() {
+            for (layer, draw) in extracted.values().flatten() {
+                layer_contents.entry(layer.clone()).or_default();
+                drawables.entry(layer).or_default().push(draw);
+            }
+        }
+
+        // let images = world.resource::<RenderAssets<GpuImage>>();
+        let mut image_slice = PxImageSliceMut::from_image_mut(image);
+        let mut stats = BTreeMap::<_, PxLayerStats>::new();
 
         #[allow(unused_variables)]
         for (
-            _,
+            layer,
             (
                 maps,
                 // image_to_sprites,
@@ -560,13 +1030,29 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
             ),
         ) in layer_contents.into_iter()
         {
+            if only_layer.is_some_and(|only_layer| *only_layer != layer) {
+                continue;
+            }
+
             layer_image.clear();
 
-            for (map, position, canvas, animation, map_filter) in maps {
+            let layer_stats = stats.entry(layer.clone()).or_default();
+            layer_stats.maps = maps.len();
+            layer_stats.sprites = sprites.len();
+            layer_stats.texts = texts.len();
+            #[cfg(feature = "line")]
+            {
+                layer_stats.lines = clip_lines.len() + over_lines.len();
+            }
+            layer_stats.filters = clip_filters.len() + over_filters.len();
+
+            for (map, position, canvas, animation, map_filter, parallax) in maps {
                 let Some(tileset) = tilesets.get(&map.tileset) else {
                     continue;
                 };
 
+                let camera = parallax.map_or(camera, |&parallax| parallax.camera(camera));
+
                 let map_filter = map_filter.and_then(|map_filter| filters.get(&**map_filter));
                 let size = map.tiles.size();
 
@@ -578,7 +1064,7 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
                             continue;
                         };
 
-                        let Ok((&PxTile { texture }, tile_filter)) =
+                        let Ok((&PxTile { texture, .. }, tile_filter)) =
                             self.tiles.get_manual(world, tile)
                         else {
                             continue;
@@ -592,7 +1078,7 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
                         draw_spatial(
                             tile,
                             (),
-                            &mut layer_image,
+                            layer_image,
                             (**position + pos.as_ivec2() * tileset.tile_size().as_ivec2()).into(),
                             PxAnchor::BottomLeft,
                             *canvas,
@@ -603,8 +1089,11 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
                             ]
                             .into_iter()
                             .flatten(),
+                            None,
                             camera,
                         );
+
+                        layer_stats.tiles += 1;
                     }
                 }
             }
@@ -748,22 +1237,62 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
             //     );
             // }
 
-            for (sprite, position, anchor, canvas, animation, filter) in sprites {
+            for (
+                sprite,
+                position,
+                anchor,
+                canvas,
+                animation,
+                filter,
+                opacity,
+                parallax,
+                rotation,
+                outline,
+                anchor_offset,
+            ) in sprites
+            {
                 let Some(sprite) = sprite_assets.get(&**sprite) else {
                     continue;
                 };
 
-                draw_spatial(
-                    sprite,
-                    (),
-                    &mut layer_image,
-                    *position,
-                    *anchor,
-                    *canvas,
-                    copy_animation_params(animation, last_update),
-                    filter.and_then(|filter| filters.get(&**filter)),
-                    camera,
-                );
+                let camera = parallax.map_or(camera, |&parallax| parallax.camera(camera));
+                let sprite = RotatedSprite(sprite, rotation.copied().unwrap_or_default());
+                let position = *position + anchor_offset.map_or(IVec2::ZERO, |offset| **offset);
+                let mut draw = |image: &mut PxImage<Option<u8>>| {
+                    draw_spatial(
+                        &sprite,
+                        (),
+                        image,
+                        position,
+                        *anchor,
+                        *canvas,
+                        copy_animation_params(animation, last_update),
+                        filter.and_then(|filter| filters.get(&**filter)),
+                        opacity.map(|opacity| (opacity, &mut *faded_image)),
+                        camera,
+                    );
+                };
+
+                match outline {
+                    Some(&PxSpriteOutline { color }) => {
+                        // Draw the sprite on its own first, so the outline can trace
+                        // its silhouette. Only the sprite and the pixels around it are touched.
+                        let rect = spatial_rect(
+                            &sprite,
+                            silhouette_image,
+                            position,
+                            *anchor,
+                            *canvas,
+                            camera,
+                        )
+                        .inflate(1);
+                        silhouette_image.slice_mut(rect).clear();
+                        draw(silhouette_image);
+                        draw_outline(silhouette_image, color, layer_image, rect);
+                        layer_image.slice_mut(rect).draw_aligned(silhouette_image);
+                    }
+                    None => draw(layer_image),
+                }
             }
 
             for (text, rect, alignment, canvas, animation, filter) in texts {
@@ -777,6 +1306,7 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
                 };
                 let rect_size = rect.size().as_uvec2();
                 let line_count = (rect_size.y + 1) / (typeface.height + 1);
+                let monospace = text.monospace(typeface);
 
                 let mut lines = Vec::default();
                 let mut line = Vec::default();
@@ -789,7 +1319,12 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
                     let (character_width, is_separator) = typeface
                         .characters
                         .get(&character)
-                        .map(|character| (character.data.width() as u32, false))
+                        .map(|character| {
+                            (
+                                monospace.unwrap_or(character.data.width() as u32),
+                                false,
+                            )
+                        })
                         .unwrap_or_else(|| {
                             (
                                 typeface
@@ -893,20 +1428,27 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
                         character_x += if let Some(character) = typeface.characters.get(&character)
                         {
                             was_character = true;
+                            let width = character.data.width() as u32;
+                            let advance = monospace.unwrap_or(width);
 
                             draw_spatial(
                                 character,
                                 (),
                                 &mut text_image,
-                                IVec2::new(character_x as i32, line_y as i32).into(),
+                                IVec2::new(
+                                    (character_x + advance.saturating_sub(width) / 2) as i32,
+                                    line_y as i32,
+                                )
+                                .into(),
                                 PxAnchor::BottomLeft,
                                 PxCanvas::Camera,
                                 copy_animation_params(animation, last_update),
                                 filter.and_then(|filter| filters.get(&**filter)),
+                                None,
                                 camera,
                             );
 
-                            character.data.width() as u32 + 1
+                            advance + 1
                         } else {
                             if was_character {
                                 character_x -= 1;
@@ -933,7 +1475,7 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
 
             // This is where I draw the line! /j
             #[cfg(feature = "line")]
-            for (line, filter, canvas, animation) in clip_lines {
+            for (line, filter, canvas, animation, style) in clip_lines {
                 if let Some(filter) = filters.get(&**filter) {
                     draw_line(
                         line,
@@ -941,11 +1483,16 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
                         &mut layer_image.slice_all_mut(),
                         *canvas,
                         copy_animation_params(animation, last_update),
+                        style,
                         camera,
                     );
                 }
             }
 
+            for draw in drawables.remove(&layer).into_iter().flatten() {
+                draw(layer_image, camera);
+            }
+
             for (filter, animation) in clip_filters {
                 if let Some(filter) = filters.get(&**filter) {
                     draw_filter(
@@ -956,10 +1503,11 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
                 }
             }
 
-            image_slice.draw(&layer_image);
+            image_slice.draw(layer_image);
+            mask_palette(palette_mask, layer_image, layer_palettes.contains(&layer));
 
             #[cfg(feature = "line")]
-            for (line, filter, canvas, animation) in over_lines {
+            for (line, filter, canvas, animation, style) in over_lines {
                 if let Some(filter) = filters.get(&**filter) {
                     draw_line(
                         line,
@@ -967,6 +1515,7 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
                         &mut image_slice,
                         *canvas,
                         copy_animation_params(animation, last_update),
+                        style,
                         camera,
                     );
                 }
@@ -983,29 +1532,150 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
             }
         }
 
+        Some(stats)
+    }
+}
+
+impl<L: PxLayer> ViewNode for PxRenderNode<L> {
+    type ViewQuery = (
+        &'static ViewTarget,
+        Option<&'static PxViewportCamera>,
+        Option<&'static ExtractedCamera>,
+    );
+
+    fn update(&mut self, world: &mut World) {
+        self.maps.update_archetypes(world);
+        self.tiles.update_archetypes(world);
+        // self.image_to_sprites.update_archetypes(world);
+        self.sprites.update_archetypes(world);
+        self.texts.update_archetypes(world);
+        #[cfg(feature = "line")]
+        self.lines.update_archetypes(world);
+        self.filters.update_archetypes(world);
+    }
+
+    fn run<'w>(
+        &self,
+        _: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (target, viewport_camera, extracted_camera): (
+            &ViewTarget,
+            Option<&PxViewportCamera>,
+            Option<&ExtractedCamera>,
+        ),
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let &LastUpdate(last_update) = world.resource::<LastUpdate>();
+        let Some(screen) = world.get_resource::<Screen>() else {
+            return Ok(());
+        };
+
+        let sprite_assets = world.resource::<RenderAssets<PxSpriteAsset>>();
+        let filters = world.resource::<RenderAssets<PxFilterAsset>>();
+
+        let camera = PxCamera(
+            viewport_camera.map_or(**world.resource::<PxCamera>(), |&camera| *camera)
+                + **world.resource::<CameraShakeOffset>(),
+        );
+        let mut buffers = world.resource::<CachedComposeBuffers>().lock().unwrap();
+
+        let Some(stats) = self.compose(
+            world,
+            last_update,
+            camera,
+            None,
+            world.resource::<LayerPalettes<L>>(),
+            world.resource::<RenderAssets<PxTileset>>(),
+            sprite_assets,
+            world.resource::<RenderAssets<PxTypeface>>(),
+            filters,
+            &mut buffers,
+        ) else {
+            return Ok(());
+        };
+
+        if let Some(state) = world.get_resource::<RenderStatsState<L>>() {
+            *state.lock().unwrap() = stats;
+        }
+
+        let ComposeBuffers {
+            image,
+            layer_image,
+            palette_mask,
+            ..
+        } = &mut *buffers;
+
+        world.resource::<ScreenshotState>().capture(
+            image,
+            palette_mask,
+            &screen.palette,
+            screen
+                .layer_palette
+                .as_ref()
+                .map_or(&screen.palette, |(_, palette)| palette),
+        );
+
         let cursor = world.resource::<CursorState>();
 
-        if let PxCursor::Filter {
-            idle,
-            left_click,
-            right_click,
-        } = world.resource()
-        {
-            if let Some(cursor_pos) = **world.resource::<PxCursorPosition>() {
-                if let Some(PxFilterAsset(filter)) = filters.get(match cursor {
-                    CursorState::Idle => idle,
-                    CursorState::Left => left_click,
-                    CursorState::Right => right_click,
-                }) {
-                    let mut image = PxImageSliceMut::from_image_mut(&mut image);
-
-                    if let Some(pixel) = image.get_pixel_mut(IVec2::new(
-                        cursor_pos.x as i32,
-                        image.height() as i32 - 1 - cursor_pos.y as i32,
-                    )) {
-                        *pixel = filter
-                            .get_pixel(IVec2::new(*pixel as i32, 0))
-                            .expect("filter is incorrect size");
+        match world.resource() {
+            PxCursor::Os => (),
+            PxCursor::Filter {
+                idle,
+                left_click,
+                right_click,
+            } => {
+                if let Some(cursor_pos) = **world.resource::<PxCursorPosition>() {
+                    if let Some(PxFilterAsset(filter)) = filters.get(match cursor {
+                        CursorState::Idle => idle,
+                        CursorState::Left => left_click,
+                        CursorState::Right => right_click,
+                    }) {
+                        let mut image = PxImageSliceMut::from_image_mut(image);
+
+                        if let Some(pixel) = image.get_pixel_mut(IVec2::new(
+                            cursor_pos.x as i32,
+                            image.height() as i32 - 1 - cursor_pos.y as i32,
+                        )) {
+                            *pixel = filter
+                                .get_pixel(IVec2::new(*pixel as i32, 0))
+                                .expect("filter is incorrect size");
+                        }
+                    }
+                }
+            }
+            PxCursor::Sprite {
+                idle,
+                left_click,
+                right_click,
+                hotspot,
+            } => {
+                if let Some(cursor_pos) = **world.resource::<PxCursorPosition>() {
+                    if let Some(sprite) = sprite_assets.get(match cursor {
+                        CursorState::Idle => idle,
+                        CursorState::Left => left_click,
+                        CursorState::Right => right_click,
+                    }) {
+                        let size = sprite.frame_size();
+                        layer_image.clear();
+
+                        draw_spatial(
+                            sprite,
+                            (),
+                            layer_image,
+                            PxPosition(IVec2::new(
+                                cursor_pos.x as i32 - hotspot.x as i32,
+                                cursor_pos.y as i32 + hotspot.y as i32 + 1 - size.y as i32,
+                            )),
+                            PxAnchor::BottomLeft,
+                            PxCanvas::Camera,
+                            None,
+                            [],
+                            None,
+                            camera,
+                        );
+
+                        PxImageSliceMut::from_image_mut(image).draw(layer_image);
+                        mask_palette(palette_mask, layer_image, false);
                     }
                 }
             }
@@ -1015,29 +1685,10 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
             return Ok(());
         };
 
-        let texture = render_context
-            .render_device()
-            .create_texture(&image.texture_descriptor);
-
-        world.resource::<RenderQueue>().write_texture(
-            texture.as_image_copy(),
-            &image.data,
-            ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(
-                    image.width() * image.texture_descriptor.format.pixel_size() as u32,
-                ),
-                rows_per_image: None,
-            },
-            image.texture_descriptor.size,
-        );
-
-        let texture_view = texture.create_view(&TextureViewDescriptor {
-            label: Some("px_texture_view"),
-            format: Some(image.texture_descriptor.format),
-            dimension: Some(TextureViewDimension::D2),
-            ..default()
-        });
+        let texture_view = upload_image(render_context, world, image, "px_texture_view");
+        mem::swap(&mut image.data, palette_mask);
+        let palette_mask_view = upload_image(render_context, world, image, "px_palette_mask_view");
+        mem::swap(&mut image.data, palette_mask);
 
         let px_pipeline = world.resource::<PxPipeline>();
         let Some(pipeline) = world
@@ -1047,26 +1698,55 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
             return Ok(());
         };
 
-        let post_process = target.post_process_write();
+        let viewport = extracted_camera.and_then(|camera| camera.viewport.as_ref());
+        let letterbox = LoadOp::Clear(world.resource::<PxLetterboxColor>().to_linear().into());
+
+        // With a viewport, draw over the main texture in place so that earlier views
+        // sharing the render target are kept
+        let (destination, load) = match world.get_resource::<PxImageTarget>() {
+            Some(image_target) => {
+                let Some(image) = world
+                    .resource::<RenderAssets<GpuImage>>()
+                    .get(&**image_target)
+                else {
+                    return Ok(());
+                };
+
+                (&image.texture_view, letterbox)
+            }
+            None if viewport.is_some() => (target.main_texture_view(), LoadOp::Load),
+            None => (target.post_process_write().destination, letterbox),
+        };
 
         let bind_group = render_context.render_device().create_bind_group(
             "px_bind_group",
             &px_pipeline.layout,
-            &BindGroupEntries::sequential((&texture_view, uniform_binding.clone())),
+            &BindGroupEntries::sequential((
+                &texture_view,
+                uniform_binding.clone(),
+                &palette_mask_view,
+            )),
         );
 
         let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
             label: Some("px_pass"),
             color_attachments: &[Some(RenderPassColorAttachment {
-                view: post_process.destination,
+                view: destination,
                 resolve_target: None,
-                ops: default(),
+                ops: Operations {
+                    load,
+                    store: StoreOp::Store,
+                },
             })],
             depth_stencil_attachment: None,
             timestamp_writes: None,
             occlusion_query_set: None,
         });
 
+        if let Some(viewport) = viewport {
+            render_pass.set_camera_viewport(viewport);
+        }
+
         render_pass.set_render_pipeline(pipeline);
         render_pass.set_bind_group(0, &bind_group, &[]);
         render_pass.draw(0..6, 0..1);
@@ -1075,6 +1755,45 @@ impl<L: PxLayer> ViewNode for PxRenderNode<L> {
     }
 }
 
+fn mask_palette(palette_mask: &mut [u8], layer_image: &PxImage<Option<u8>>, layer_palette: bool) {
+    for (mask, pixel) in palette_mask.iter_mut().zip(layer_image.iter()) {
+        if pixel.is_some() {
+            *mask = layer_palette as u8;
+        }
+    }
+}
+
+fn upload_image(
+    render_context: &RenderContext,
+    world: &World,
+    image: &Image,
+    label: &'static str,
+) -> TextureView {
+    let texture = render_context
+        .render_device()
+        .create_texture(&image.texture_descriptor);
+
+    world.resource::<RenderQueue>().write_texture(
+        texture.as_image_copy(),
+        &image.data,
+        ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(
+                image.width() * image.texture_descriptor.format.pixel_size() as u32,
+            ),
+            rows_per_image: None,
+        },
+        image.texture_descriptor.size,
+    );
+
+    texture.create_view(&TextureViewDescriptor {
+        label: Some(label),
+        format: Some(image.texture_descriptor.format),
+        dimension: Some(TextureViewDimension::D2),
+        ..default()
+    })
+}
+
 fn update_screen_palette(
     mut waiting_for_load: Local<bool>,
     palette_handle: Res<PaletteHandle>,
@@ -1100,3 +1819,127 @@ fn update_screen_palette(
 
     *waiting_for_load = false;
 }
+
+fn update_palette_transition(
+    mut waiting_for_load: Local<bool>,
+    transition: Res<PxPaletteTransition>,
+    palette_handle: Res<PaletteHandle>,
+    palettes: Res<Assets<Palette>>,
+    mut screen: ResMut<Screen>,
+) {
+    if !transition.is_changed() && !palette_handle.is_changed() && !*waiting_for_load {
+        return;
+    }
+
+    let (Some(from), Some(to)) = (palettes.get(&transition.from), palettes.get(&transition.to))
+    else {
+        *waiting_for_load = true;
+        return;
+    };
+
+    let progress = transition.progress.clamp(0., 1.);
+    let mut screen_palette = [Vec3::ZERO; 256];
+
+    for (i, ([from_r, from_g, from_b], [to_r, to_g, to_b])) in
+        from.colors.iter().zip(&to.colors).enumerate()
+    {
+        screen_palette[i] = Color::srgb_u8(*from_r, *from_g, *from_b)
+            .to_linear()
+            .to_vec3()
+            .lerp(
+                Color::srgb_u8(*to_r, *to_g, *to_b).to_linear().to_vec3(),
+                progress,
+            );
+    }
+
+    screen.palette = screen_palette;
+
+    *waiting_for_load = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::position::InsertDefaultLayer;
+
+    use super::*;
+
+    #[px_layer]
+    struct Layer;
+
+    // Builds a world with a 4x3 screen and everything `compose_screen` reads
+    fn scene() -> World {
+        let mut world = World::new();
+        let size = ScreenSize::from(UVec2::new(4, 3));
+
+        world.insert_resource(Screen {
+            size,
+            computed_size: size.compute(Vec2::ONE),
+            window_aspect_ratio: 1.,
+            window_size: UVec2::ONE,
+            scaling: default(),
+            palette: [Vec3::ZERO; 256],
+            layer_palette: None,
+        });
+        world.init_resource::<PxBackgroundColor>();
+        world.init_resource::<PxCamera>();
+        world.init_resource::<CameraShakeOffset>();
+        world.init_resource::<Assets<PxTileset>>();
+        world.init_resource::<Assets<PxSpriteAsset>>();
+        world.init_resource::<Assets<PxTypeface>>();
+        world.init_resource::<Assets<PxFilterAsset>>();
+        world.insert_resource(InsertDefaultLayer::new::<Layer>());
+        world
+    }
+
+    #[test]
+    fn compose_sprite() {
+        let mut world = scene();
+        let sprite = world
+            .resource_mut::<Assets<PxSpriteAsset>>()
+            .add(PxSpriteAsset {
+                data: PxImage::new(vec![Some(1), None, Some(2), Some(3)], 2),
+                frame_size: 4,
+                trim: None,
+            });
+
+        world.spawn((
+            PxSprite(sprite),
+            PxPosition(IVec2::ONE),
+            PxAnchor::BottomLeft,
+        ));
+        // The layer is inserted by a command
+        world.flush();
+
+        assert_px_image_eq!(
+            compose_screen::<Layer>(&mut world).unwrap(),
+            [[0, 1, 0, 0], [0, 2, 3, 0], [0, 0, 0, 0]],
+        );
+    }
+
+    #[test]
+    fn compose_outlined_sprites() {
+        let mut world = scene();
+        let sprite = world
+            .resource_mut::<Assets<PxSpriteAsset>>()
+            .add(PxSpriteAsset {
+                data: PxImage::new(vec![Some(1)], 1),
+                frame_size: 1,
+                trim: None,
+            });
+
+        for position in [IVec2::ONE, IVec2::new(3, 0)] {
+            world.spawn((
+                PxSprite(sprite.clone()),
+                PxPosition(position),
+                PxAnchor::BottomLeft,
+                PxSpriteOutline { color: 5 },
+            ));
+        }
+        world.flush();
+
+        assert_px_image_eq!(
+            compose_screen::<Layer>(&mut world).unwrap(),
+            [[0, 5, 0, 0], [5, 1, 5, 5], [0, 5, 5, 1]],
+        );
+    }
+}
@@ -1,10 +1,11 @@
 struct PxUniform {
-    palette: array<vec3<f32>, 256>,
+    palettes: array<array<vec3<f32>, 256>, 2>,
     fit_factor: vec2<f32>,
 };
 
 @group(0) @binding(0) var texture: texture_2d<u32>;
 @group(0) @binding(1) var<uniform> uniform: PxUniform;
+@group(0) @binding(2) var palette_mask: texture_2d<u32>;
 
 struct VertexOut {
     @builtin(position) position: vec4<f32>,
@@ -19,7 +20,9 @@ var<private> VERTEX_U: array<f32, 6> = array(0., 0., 1., 0., 1., 1.);
 }
 
 @fragment fn fragment(vert: VertexOut) -> @location(0) vec4<f32> {
-    return vec4(uniform.palette[
-        textureLoad(texture, vec2<i32>(vec2<f32>(textureDimensions(texture)) * vert.uv), 0).r
-    ], 1.);
+    let pos = vec2<i32>(vec2<f32>(textureDimensions(texture)) * vert.uv);
+    let index = textureLoad(texture, pos, 0).r;
+    let palette = min(textureLoad(palette_mask, pos, 0).r, 1u);
+
+    return vec4(uniform.palettes[palette][index], 1.);
 }
@@ -0,0 +1,102 @@
+//! Screenshots
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use bevy::render::{
+    extract_resource::{ExtractResource, ExtractResourcePlugin},
+    render_asset::RenderAssetUsages,
+    render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::prelude::*;
+
+pub(crate) fn plug(app: &mut App) {
+    app.add_plugins(ExtractResourcePlugin::<ScreenshotState>::default())
+        .add_event::<PxScreenshot>()
+        .add_event::<PxScreenshotTaken>()
+        .init_resource::<ScreenshotState>()
+        .add_systems(PostUpdate, request_screenshots)
+        .add_systems(First, send_screenshots);
+}
+
+/// Send this event to take a screenshot of the screen at its pixel resolution, colored
+/// with the current palette. The in-game cursor is not included. The screenshot is delivered
+/// in a [`PxScreenshotTaken`] event, usually on the next frame.
+#[derive(Event, Clone, Copy, Default, Debug)]
+pub struct PxScreenshot;
+
+/// Sent when a screenshot requested with [`PxScreenshot`] is ready
+#[derive(Event, Clone, Debug)]
+pub struct PxScreenshotTaken {
+    /// The screenshot, in `Rgba8UnormSrgb`
+    pub image: Handle<Image>,
+}
+
+#[derive(ExtractResource, Resource, Clone, Default)]
+pub(crate) struct ScreenshotState {
+    requested: Arc<AtomicBool>,
+    taken: Arc<Mutex<Option<Image>>>,
+}
+
+impl ScreenshotState {
+    /// Takes the screenshot if one has been requested
+    pub(crate) fn capture(
+        &self,
+        image: &Image,
+        palette_mask: &[u8],
+        palette: &[Vec3; 256],
+        layer_palette: &[Vec3; 256],
+    ) {
+        if !self.requested.swap(false, Ordering::Relaxed) {
+            return;
+        }
+
+        let data = image
+            .data
+            .iter()
+            .zip(palette_mask)
+            .flat_map(|(&index, &mask)| {
+                let color = match mask {
+                    0 => palette,
+                    _ => layer_palette,
+                }[index as usize];
+                Srgba::from(LinearRgba::rgb(color.x, color.y, color.z)).to_u8_array()
+            })
+            .collect();
+
+        *self.taken.lock().unwrap() = Some(Image::new(
+            Extent3d {
+                width: image.width(),
+                height: image.height(),
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::all(),
+        ));
+    }
+}
+
+fn request_screenshots(mut screenshots: EventReader<PxScreenshot>, state: Res<ScreenshotState>) {
+    if screenshots.read().last().is_some() {
+        state.requested.store(true, Ordering::Relaxed);
+    }
+}
+
+fn send_screenshots(
+    state: Res<ScreenshotState>,
+    mut images: ResMut<Assets<Image>>,
+    mut taken: EventWriter<PxScreenshotTaken>,
+) {
+    let Some(image) = state.taken.lock().unwrap().take() else {
+        return;
+    };
+
+    taken.send(PxScreenshotTaken {
+        image: images.add(image),
+    });
+}
@@ -2,6 +2,19 @@
 
 use crate::prelude::*;
 
+pub(crate) fn plug(app: &mut App) {
+    app.configure_sets(
+        PostUpdate,
+        PxSet::PreExtract
+            .after(PxSet::UpdateButtonAssets)
+            .after(PxSet::UpdateCamera)
+            .after(PxSet::FinishAnimations),
+    );
+
+    #[cfg(feature = "particle")]
+    app.configure_sets(PostUpdate, PxSet::PreExtract.after(PxSet::UpdateEmitters));
+}
+
 // TODO Many of these aren't necessary anymore
 /// Sets used by this crate
 #[derive(Clone, Debug, Eq, Hash, PartialEq, SystemSet)]
@@ -9,7 +22,8 @@ pub enum PxSet {
     // `PreUpdate`
     /// The [`PxPosition`] is updated to match [`PxSubPosition`]. In [`CoreSet::PreUpdate`].
     UpdatePosToSubPos,
-    /// [`crate::cursor::PxCursorPosition`] is updated. In [`CoreSet::PreUpdate`].
+    /// [`crate::cursor::PxCursorPosition`] and [`PxCursorWorldPosition`] are updated.
+    /// In [`CoreSet::PreUpdate`].
     UpdateCursorPosition,
 
     // `PostUpdate`
@@ -17,9 +31,16 @@ pub enum PxSet {
     AddButtonAssets,
     /// Button assets are updated. In [`CoreSet::PostUpdate`].
     UpdateButtonAssets,
+    /// [`PxCamera`] is moved by [`PxCameraFollow`] and clamped to [`PxCameraBounds`],
+    /// and [`PxCameraShake`] is applied. In [`CoreSet::PostUpdate`].
+    UpdateCamera,
     /// Animations are completed. In [`CoreSet::PostUpdate`].
     FinishAnimations,
     /// Update particle emitters. In [`CoreSet::PostUpdate`].
     #[cfg(feature = "particle")]
     UpdateEmitters,
+    /// Runs after this crate's other [`CoreSet::PostUpdate`] sets, right before the frame is
+    /// extracted for rendering. Add systems here to change what gets drawn this frame, such as
+    /// positions that depend on the camera. In [`CoreSet::PostUpdate`].
+    PreExtract,
 }
@@ -1,6 +1,6 @@
 //! Sprites
 
-use anyhow::{Error, Result};
+use anyhow::{anyhow, Error, Result};
 use bevy::{
     asset::{io::Reader, AssetLoader, LoadContext},
     image::{CompressedImageFormats, ImageLoader, ImageLoaderSettings},
@@ -15,11 +15,13 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     animation::{AnimatedAssetComponent, Animation},
-    image::{PxImage, PxImageSliceMut},
-    palette::asset_palette,
+    dither::{dither_image, PxDither},
+    image::{decode_png, PxImage, PxImageSliceMut},
+    palette::{asset_palette, Palette},
     pixel::Pixel,
     position::{DefaultLayer, PxLayer, Spatial},
     prelude::*,
+    screen::Screen,
 };
 
 pub(crate) fn plug<L: PxLayer>(app: &mut App) {
@@ -39,9 +41,31 @@ pub(crate) fn plug<L: PxLayer>(app: &mut App) {
     );
 }
 
+// How an animated sprite's frames are laid out in its image
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+enum FrameLayout {
+    // Frames are stacked from top to bottom
+    #[default]
+    Vertical,
+    // Frames are placed from left to right
+    Horizontal,
+    // Frames are placed left to right, then top to bottom, in rows of `columns` frames
+    Grid {
+        columns: usize,
+    },
+}
+
 #[derive(Serialize, Deserialize)]
 struct PxSpriteLoaderSettings {
     frame_count: usize,
+    #[serde(default)]
+    frame_layout: FrameLayout,
+    // Color that is loaded as transparent, for sprites that use a color key instead of alpha
+    #[serde(default)]
+    transparent_color: Option<[u8; 3]>,
+    // Whether to trim transparent rows and columns from the edges of the frames
+    #[serde(default)]
+    auto_trim: bool,
     image_loader_settings: ImageLoaderSettings,
 }
 
@@ -49,6 +73,9 @@ impl Default for PxSpriteLoaderSettings {
     fn default() -> Self {
         Self {
             frame_count: 1,
+            frame_layout: default(),
+            transparent_color: None,
+            auto_trim: false,
             image_loader_settings: default(),
         }
     }
@@ -71,13 +98,8 @@ impl AssetLoader for PxSpriteLoader {
         let image = ImageLoader::new(CompressedImageFormats::NONE)
             .load(reader, &settings.image_loader_settings, load_context)
             .await?;
-        let palette = asset_palette().await;
-        let data = PxImage::palette_indices(palette, &image)?;
 
-        Ok(PxSpriteAsset {
-            frame_size: data.area() / settings.frame_count,
-            data,
-        })
+        load_sprite(&image, asset_palette().await, settings)
     }
 
     fn extensions(&self) -> &[&str] {
@@ -85,14 +107,143 @@ impl AssetLoader for PxSpriteLoader {
     }
 }
 
+fn load_sprite(
+    image: &Image,
+    palette: &Palette,
+    settings: &PxSpriteLoaderSettings,
+) -> Result<PxSpriteAsset> {
+    if settings.frame_count == 0 {
+        return Err(anyhow!("sprite has a `frame_count` of 0"));
+    }
+
+    let data = PxImage::palette_indices(palette, image, settings.transparent_color)?;
+
+    // Frames are drawn from a vertical strip, so rearrange other layouts into one
+    let columns = match settings.frame_layout {
+        FrameLayout::Vertical => 1,
+        FrameLayout::Horizontal => settings.frame_count,
+        FrameLayout::Grid { columns } => columns.clamp(1, settings.frame_count),
+    };
+    let data = if columns == 1 {
+        data
+    } else {
+        let rows = settings.frame_count.div_ceil(columns);
+        let row_height = data.height() / rows;
+        let frame_width = data.width() / columns;
+
+        if row_height == 0 || frame_width == 0 {
+            return Err(anyhow!("sprite is too small for its frame layout"));
+        }
+
+        if !data.width().is_multiple_of(columns) || !data.height().is_multiple_of(rows) {
+            return Err(anyhow!(
+                "sprite's size of {}x{} isn't divisible into {columns} columns and {rows} rows",
+                data.width(),
+                data.height(),
+            ));
+        }
+
+        PxImage::from_parts_vert(
+            data.split_vert(row_height)
+                .into_iter()
+                .flat_map(|row| row.split_horz(frame_width))
+                .take(settings.frame_count),
+        )
+        .ok_or_else(|| anyhow!("sprite frames have inconsistent sizes"))?
+    };
+
+    if data.height() % settings.frame_count != 0 {
+        return Err(anyhow!(
+            "sprite's height of {} isn't divisible by its `frame_count` of {}",
+            data.height(),
+            settings.frame_count
+        ));
+    }
+
+    let frame_size = data.area() / settings.frame_count;
+    let (data, frame_size, trim) = match settings.auto_trim {
+        true => trim_frames(data, frame_size),
+        false => (data, frame_size, None),
+    };
+
+    Ok(PxSpriteAsset {
+        data,
+        frame_size,
+        trim,
+    })
+}
+
+// Transparent margins trimmed from a sprite's frames when it was loaded
+#[derive(Serialize, Deserialize, Clone, Copy, Reflect, Debug)]
+pub(crate) struct SpriteTrim {
+    // Frame size before trimming
+    size: UVec2,
+    // Bottom-left of the trimmed frame within the untrimmed frame, with y up
+    offset: UVec2,
+}
+
+// Crops every frame to the smallest rectangle that contains all frames' opaque pixels,
+// so the frames stay aligned with each other
+fn trim_frames(
+    data: PxImage<Option<u8>>,
+    frame_size: usize,
+) -> (PxImage<Option<u8>>, usize, Option<SpriteTrim>) {
+    let width = data.width();
+    let frame_height = frame_size / width;
+    let mut min = UVec2::MAX;
+    let mut max = UVec2::ZERO;
+
+    for (i, pixel) in data.iter().enumerate() {
+        if pixel.is_some() {
+            let pos = UVec2::new((i % width) as u32, ((i / width) % frame_height) as u32);
+            min = min.min(pos);
+            max = max.max(pos);
+        }
+    }
+
+    let size = UVec2::new(width as u32, frame_height as u32);
+    // Leave fully transparent and already tight sprites as they are
+    if min.x > max.x || max - min + UVec2::ONE == size {
+        return (data, frame_size, None);
+    }
+
+    let trimmed_size = max - min + UVec2::ONE;
+    let frame_count = data.height() / frame_height;
+    let mut trimmed = Vec::with_capacity(trimmed_size.element_product() as usize * frame_count);
+
+    for frame in 0..frame_count {
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                trimmed.push(data.pixel(IVec2::new(
+                    x as i32,
+                    (frame * frame_height) as i32 + y as i32,
+                )));
+            }
+        }
+    }
+
+    (
+        PxImage::new(trimmed, trimmed_size.x as usize),
+        trimmed_size.element_product() as usize,
+        Some(SpriteTrim {
+            size,
+            offset: UVec2::new(min.x, size.y - 1 - max.y),
+        }),
+    )
+}
+
 /// A sprite. Create a [`Handle<PxSpriteAsset>`] with a [`PxAssets<PxSprite>`] and an image.
-/// If the sprite is animated, the frames should be laid out from top to bottom.
-/// See `assets/sprite/runner.png` for an example of an animated sprite.
+/// If the sprite is animated, the frames should be laid out from top to bottom, unless
+/// the `.meta` file's `frame_layout` is `Horizontal` or `Grid(columns: ..)`. Set `auto_trim: true`
+/// in the `.meta` file to trim transparent margins from the frames. Trimmed sprites are still
+/// anchored as if they had their full size. See `assets/sprite/runner.png` for an example of an animated sprite.
 #[derive(Asset, Serialize, Deserialize, Clone, Reflect, Debug)]
 pub struct PxSpriteAsset {
     // TODO Use 0 for transparency
     pub(crate) data: PxImage<Option<u8>>,
     pub(crate) frame_size: usize,
+    #[serde(default)]
+    pub(crate) trim: Option<SpriteTrim>,
 }
 
 impl RenderAsset for PxSpriteAsset {
@@ -118,20 +269,133 @@ impl Animation for PxSpriteAsset {
         &self,
         _: (),
         image: &mut PxImageSliceMut<impl Pixel>,
-        frame: impl Fn(UVec2) -> usize,
-        filter: impl Fn(u8) -> u8,
+        frame: impl Fn(UVec2) -> usize + Sync,
+        filter: impl Fn(u8) -> u8 + Sync,
+    ) {
+        self.draw_rotated(PxRotation::None, image, frame, filter);
+    }
+}
+
+impl Spatial for PxSpriteAsset {
+    fn frame_size(&self) -> UVec2 {
+        UVec2::new(
+            self.data.width() as u32,
+            (self.frame_size / self.data.width()) as u32,
+        )
+    }
+
+    fn anchor_pos(&self, anchor: PxAnchor) -> IVec2 {
+        RotatedSprite(self, PxRotation::None).anchor_pos(anchor)
+    }
+}
+
+impl PxSpriteAsset {
+    /// Converts a full-color image to a single-frame sprite, such as for importing a photo.
+    /// Each color is mapped to the nearest color in the palette, optionally with dithering.
+    /// Pixels with less than half alpha are transparent.
+    pub fn from_image(image: &Image, palette: &Palette, dither: Option<PxDither>) -> Result<Self> {
+        if image.texture_descriptor.size.width == 0 {
+            return Err(anyhow!("image has a width of 0"));
+        }
+
+        let data = PxImage::new(
+            dither_image(image, palette, dither)?,
+            image.texture_descriptor.size.width as usize,
+        );
+
+        Ok(Self {
+            frame_size: data.area(),
+            data,
+            trim: None,
+        })
+    }
+
+    /// Creates a sprite from the bytes of a PNG, such as one embedded with [`include_bytes!`],
+    /// without the asset server. Frames are laid out from top to bottom.
+    pub fn from_png_bytes(bytes: &[u8], palette: &Palette, frame_count: usize) -> Result<Self> {
+        load_sprite(
+            &decode_png(bytes)?,
+            palette,
+            &PxSpriteLoaderSettings {
+                frame_count,
+                ..default()
+            },
+        )
+    }
+
+    /// Draws the given frame of this sprite onto `image`, with its bottom-left corner
+    /// at `position`. Like [`PxPosition`], `position` has y up.
+    pub fn draw_frame(&self, image: &mut PxImage<impl Pixel>, position: IVec2, frame: usize) {
+        let size = self.frame_size().as_ivec2();
+        let min = IVec2::new(position.x, image.size().y as i32 - position.y - size.y);
+
+        self.draw(
+            (),
+            &mut image.slice_mut(IRect {
+                min,
+                max: min + size,
+            }),
+            |_| frame,
+            |pixel| pixel,
+        );
+    }
+
+    /// Copies the given frame into a new single-frame sprite, such as for using one frame
+    /// of an animation as an icon. The copy keeps this sprite's trim, so it's anchored
+    /// the same way.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` isn't less than the sprite's frame count
+    pub fn frame(&self, index: usize) -> PxSpriteAsset {
+        let frame_count = self.frame_count();
+        assert!(
+            index < frame_count,
+            "frame index out of bounds: the frame count is {frame_count}, but the index is {index}"
+        );
+
+        let data = self
+            .data
+            .clone()
+            .split_vert(self.frame_size / self.data.width())
+            .swap_remove(index);
+
+        PxSpriteAsset {
+            frame_size: data.area(),
+            data,
+            trim: self.trim,
+        }
+    }
+
+    fn draw_rotated(
+        &self,
+        rotation: PxRotation,
+        image: &mut PxImageSliceMut<impl Pixel>,
+        frame: impl Fn(UVec2) -> usize + Sync,
+        filter: impl Fn(u8) -> u8 + Sync,
     ) {
-        let width = self.data.width();
+        let size = self.frame_size().as_ivec2();
+        let width = rotation.size(size.as_uvec2()).x as usize;
         let image_width = image.image_width();
         image.for_each_mut(|slice_i, image_i, pixel| {
+            let x = (slice_i % width) as i32;
+            let y = (slice_i / width) as i32;
+            // Position in the unrotated frame, with y down
+            let source = match rotation {
+                PxRotation::None => IVec2::new(x, y),
+                PxRotation::Quarter => IVec2::new(y, size.y - 1 - x),
+                PxRotation::Half => IVec2::new(size.x - 1 - x, size.y - 1 - y),
+                PxRotation::ThreeQuarter => IVec2::new(size.x - 1 - y, x),
+            };
+
             if let Some(Some(value)) = self.data.get_pixel(IVec2::new(
-                (slice_i % width) as i32,
-                ((frame(UVec2::new(
+                source.x,
+                frame(UVec2::new(
                     (image_i % image_width) as u32,
                     (image_i / image_width) as u32,
-                )) * self.frame_size
-                    + slice_i)
-                    / width) as i32,
+                )) as i32
+                    * size.y
+                    + source.y,
             )) {
                 pixel.set_value(filter(value));
             }
@@ -139,12 +403,101 @@ impl Animation for PxSpriteAsset {
     }
 }
 
-impl Spatial for PxSpriteAsset {
+/// Rotates a sprite clockwise by a multiple of 90 degrees
+#[derive(Component, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum PxRotation {
+    /// Not rotated
+    #[default]
+    None,
+    /// Rotated 90 degrees clockwise
+    Quarter,
+    /// Rotated 180 degrees
+    Half,
+    /// Rotated 270 degrees clockwise, or 90 degrees counterclockwise
+    ThreeQuarter,
+}
+
+impl PxRotation {
+    /// Size of an image with the given size after being rotated
+    pub fn size(self, size: UVec2) -> UVec2 {
+        match self {
+            PxRotation::None | PxRotation::Half => size,
+            PxRotation::Quarter | PxRotation::ThreeQuarter => UVec2::new(size.y, size.x),
+        }
+    }
+}
+
+/// Draws a 1 pixel outline around a sprite's silhouette, like a selection highlight
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PxSpriteOutline {
+    /// The outline's color
+    pub color: u8,
+}
+
+/// Draws `color` on each transparent pixel of `silhouette` within `rect` that's next
+/// to an opaque pixel
+pub(crate) fn draw_outline(
+    silhouette: &PxImage<Option<u8>>,
+    color: u8,
+    image: &mut PxImage<Option<u8>>,
+    rect: IRect,
+) {
+    let width = silhouette.width();
+    image.slice_mut(rect).for_each_mut(|_, i, pixel| {
+        let position = IVec2::new((i % width) as i32, (i / width) as i32);
+
+        if silhouette.pixel(position).is_none()
+            && [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+                .into_iter()
+                .any(|offset| matches!(silhouette.get_pixel(position + offset), Some(Some(_))))
+        {
+            *pixel = Some(color);
+        }
+    });
+}
+
+/// A sprite asset drawn with a [`PxRotation`]
+pub(crate) struct RotatedSprite<'a>(pub(crate) &'a PxSpriteAsset, pub(crate) PxRotation);
+
+impl Animation for RotatedSprite<'_> {
+    type Param = ();
+
+    fn frame_count(&self) -> usize {
+        self.0.frame_count()
+    }
+
+    fn draw(
+        &self,
+        _: (),
+        image: &mut PxImageSliceMut<impl Pixel>,
+        frame: impl Fn(UVec2) -> usize + Sync,
+        filter: impl Fn(u8) -> u8 + Sync,
+    ) {
+        self.0.draw_rotated(self.1, image, frame, filter);
+    }
+}
+
+impl Spatial for RotatedSprite<'_> {
     fn frame_size(&self) -> UVec2 {
-        UVec2::new(
-            self.data.width() as u32,
-            (self.frame_size / self.data.width()) as u32,
-        )
+        self.1.size(self.0.frame_size())
+    }
+
+    fn anchor_pos(&self, anchor: PxAnchor) -> IVec2 {
+        let &RotatedSprite(sprite, rotation) = self;
+        let Some(SpriteTrim { size, offset }) = sprite.trim else {
+            return anchor.pos(self.frame_size()).as_ivec2();
+        };
+
+        // Rotate the trimmed frame's bounds within the untrimmed frame along with the sprite
+        let trimmed_size = sprite.frame_size();
+        let offset = match rotation {
+            PxRotation::None => offset,
+            PxRotation::Quarter => UVec2::new(offset.y, size.x - offset.x - trimmed_size.x),
+            PxRotation::Half => size - offset - trimmed_size,
+            PxRotation::ThreeQuarter => UVec2::new(size.y - offset.y - trimmed_size.y, offset.x),
+        };
+
+        anchor.pos(rotation.size(size)).as_ivec2() - offset.as_ivec2()
     }
 }
 
@@ -159,6 +512,32 @@ impl From<Handle<PxSpriteAsset>> for PxSprite {
     }
 }
 
+/// Makes a sprite partially transparent by dithering it. Ranges from 0 (invisible)
+/// to 1 (fully opaque). Since colors can't be blended, there are only 16 distinct levels
+/// of transparency.
+#[derive(Component, Deref, DerefMut, Clone, Copy, Debug)]
+pub struct PxOpacity(pub f32);
+
+impl Default for PxOpacity {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl From<f32> for PxOpacity {
+    fn from(opacity: f32) -> Self {
+        Self(opacity)
+    }
+}
+
+/// Stops drawing a sprite when it's farther than `max` pixels from the center of the camera.
+/// Has no effect on sprites with [`PxCanvas::Camera`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PxVisibilityRange {
+    /// Maximum distance from the center of the camera, in pixels
+    pub max: f32,
+}
+
 impl AnimatedAssetComponent for PxSprite {
     type Asset = PxSpriteAsset;
 
@@ -400,22 +779,69 @@ pub(crate) type SpriteComponents<L> = (
     &'static PxCanvas,
     Option<&'static PxAnimation>,
     Option<&'static PxFilter>,
+    Option<&'static PxOpacity>,
+    Option<&'static PxParallax>,
+    Option<&'static PxRotation>,
+    Option<&'static PxSpriteOutline>,
+    Option<&'static PxAnchorOffset>,
 );
 
 fn extract_sprites<L: PxLayer>(
     // TODO Maybe calculate `ViewVisibility`
-    sprites: Extract<Query<(SpriteComponents<L>, &InheritedVisibility, RenderEntity)>>,
+    sprites: Extract<
+        Query<(
+            SpriteComponents<L>,
+            Option<&PxVisibilityRange>,
+            &InheritedVisibility,
+            Has<PxHidden>,
+            RenderEntity,
+        )>,
+    >,
+    camera: Extract<Res<PxCamera>>,
+    screen: Extract<Option<Res<Screen>>>,
     mut cmd: Commands,
 ) {
-    for ((sprite, &position, &anchor, layer, &canvas, animation, filter), visibility, id) in
-        &sprites
+    let camera_center = screen
+        .as_ref()
+        .map(|screen| camera.as_vec2() + screen.computed_size.as_vec2() / 2.);
+
+    for (
+        (
+            sprite,
+            &position,
+            &anchor,
+            layer,
+            &canvas,
+            animation,
+            filter,
+            opacity,
+            parallax,
+            rotation,
+            outline,
+            anchor_offset,
+        ),
+        range,
+        visibility,
+        hidden,
+        id,
+    ) in &sprites
     {
-        if !visibility.get() {
+        if hidden || !visibility.get() {
+            cmd.entity(id).insert(PxHidden);
             continue;
         }
 
+        if let (Some(range), Some(camera_center), PxCanvas::World) = (range, camera_center, canvas)
+        {
+            if position.as_vec2().distance(camera_center) > range.max {
+                cmd.entity(id).insert(PxHidden);
+                continue;
+            }
+        }
+
         let mut entity = cmd.entity(id);
         entity.insert((sprite.clone(), position, anchor, layer.clone(), canvas));
+        entity.remove::<PxHidden>();
 
         if let Some(animation) = animation {
             entity.insert(*animation);
@@ -428,6 +854,36 @@ fn extract_sprites<L: PxLayer>(
         } else {
             entity.remove::<PxFilter>();
         }
+
+        if let Some(&opacity) = opacity {
+            entity.insert(opacity);
+        } else {
+            entity.remove::<PxOpacity>();
+        }
+
+        if let Some(&parallax) = parallax {
+            entity.insert(parallax);
+        } else {
+            entity.remove::<PxParallax>();
+        }
+
+        if let Some(&rotation) = rotation {
+            entity.insert(rotation);
+        } else {
+            entity.remove::<PxRotation>();
+        }
+
+        if let Some(&outline) = outline {
+            entity.insert(outline);
+        } else {
+            entity.remove::<PxSpriteOutline>();
+        }
+
+        if let Some(&anchor_offset) = anchor_offset {
+            entity.insert(anchor_offset);
+        } else {
+            entity.remove::<PxAnchorOffset>();
+        }
     }
 }
 
@@ -464,3 +920,54 @@ fn extract_sprites<L: PxLayer>(
 //         }
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use super::*;
+
+    fn image(size: UVec2) -> Image {
+        Image::new_fill(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[255, 255, 255, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            default(),
+        )
+    }
+
+    fn load(size: UVec2, frame_count: usize, frame_layout: FrameLayout) -> Result<PxSpriteAsset> {
+        load_sprite(
+            &image(size),
+            &Palette::from_colors([[255, 255, 255]], 1),
+            &PxSpriteLoaderSettings {
+                frame_count,
+                frame_layout,
+                ..default()
+            },
+        )
+    }
+
+    #[test]
+    fn grid_layout_loads_even_sheet() {
+        let sprite = load(UVec2::new(6, 4), 4, FrameLayout::Grid { columns: 2 }).unwrap();
+        assert_eq!(sprite.frame_size(), UVec2::new(3, 2));
+        assert_eq!(sprite.frame_count(), 4);
+    }
+
+    #[test]
+    fn grid_layout_rejects_uneven_sheet() {
+        assert!(load(UVec2::new(7, 4), 4, FrameLayout::Grid { columns: 2 }).is_err());
+        assert!(load(UVec2::new(6, 5), 4, FrameLayout::Grid { columns: 2 }).is_err());
+    }
+
+    #[test]
+    fn horizontal_layout_rejects_uneven_sheet() {
+        assert!(load(UVec2::new(7, 2), 3, FrameLayout::Horizontal).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! Render statistics
+
+#[cfg(feature = "stats_overlay")]
+use std::fmt::Write;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+
+use crate::{position::PxLayer, prelude::*};
+
+pub(crate) fn plug<L: PxLayer>(app: &mut App) {
+    app.add_plugins(ExtractResourcePlugin::<RenderStatsState<L>>::default())
+        .init_resource::<RenderStatsState<L>>()
+        .init_resource::<PxRenderStats<L>>()
+        .add_systems(First, update_render_stats::<L>);
+
+    #[cfg(feature = "stats_overlay")]
+    app.add_systems(PostUpdate, update_stats_overlays::<L>);
+}
+
+/// How much was drawn on a layer
+#[derive(Clone, Copy, Default, Debug)]
+pub struct PxLayerStats {
+    /// Number of maps
+    pub maps: usize,
+    /// Number of tiles drawn from the maps
+    pub tiles: usize,
+    /// Number of sprites
+    pub sprites: usize,
+    /// Number of texts
+    pub texts: usize,
+    /// Number of lines
+    pub lines: usize,
+    /// Number of layer filters
+    pub filters: usize,
+}
+
+/// How much was drawn on each layer during the last rendered frame. Useful for finding layers
+/// that are unexpectedly heavy. Empty layers are not included.
+#[derive(Resource, Deref, Debug)]
+pub struct PxRenderStats<L: PxLayer>(pub BTreeMap<L, PxLayerStats>);
+
+impl<L: PxLayer> Default for PxRenderStats<L> {
+    fn default() -> Self {
+        Self(default())
+    }
+}
+
+#[derive(Resource, Deref)]
+pub(crate) struct RenderStatsState<L: PxLayer>(Arc<Mutex<BTreeMap<L, PxLayerStats>>>);
+
+impl<L: PxLayer> Clone for RenderStatsState<L> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<L: PxLayer> Default for RenderStatsState<L> {
+    fn default() -> Self {
+        Self(default())
+    }
+}
+
+impl<L: PxLayer> ExtractResource for RenderStatsState<L> {
+    type Source = Self;
+
+    fn extract_resource(source: &Self) -> Self {
+        source.clone()
+    }
+}
+
+fn update_render_stats<L: PxLayer>(
+    state: Res<RenderStatsState<L>>,
+    mut stats: ResMut<PxRenderStats<L>>,
+) {
+    stats.0.clone_from(&state.lock().unwrap());
+}
+
+/// Displays [`PxRenderStats`] in the entity's [`PxText`]. Requires the `stats_overlay` feature.
+#[cfg(feature = "stats_overlay")]
+#[derive(Component, Default, Clone, Copy, Debug)]
+#[require(PxText)]
+pub struct PxStatsOverlay;
+
+#[cfg(feature = "stats_overlay")]
+fn update_stats_overlays<L: PxLayer>(
+    mut overlays: Query<&mut PxText, With<PxStatsOverlay>>,
+    stats: Res<PxRenderStats<L>>,
+) {
+    let mut value = String::new();
+
+    for (layer, stats) in &**stats {
+        if !value.is_empty() {
+            value.push(' ');
+        }
+
+        // Text doesn't support line breaks, so each layer starts with its name
+        write!(
+            value,
+            "{layer:?} MAPS {} TILES {} SPRITES {} TEXTS {} LINES {} FILTERS {}",
+            stats.maps, stats.tiles, stats.sprites, stats.texts, stats.lines, stats.filters,
+        )
+        .unwrap();
+    }
+
+    for mut text in &mut overlays {
+        if text.value != value {
+            text.value.clone_from(&value);
+        }
+    }
+}
@@ -67,12 +67,16 @@ impl AssetLoader for PxTypefaceLoader {
             .load(reader, &settings.image_loader_settings, load_context)
             .await?;
         let palette = asset_palette().await;
-        let indices = PxImage::palette_indices(palette, &image)?;
+        let indices = PxImage::palette_indices(palette, &image, None)?;
         let height = indices.height();
         let character_count = settings.characters.chars().count();
 
         let characters = if character_count == 0 {
             HashMap::new()
+        } else if height < character_count {
+            return Err(anyhow!(
+                "typeface's height of {height} is less than its {character_count} characters"
+            ));
         } else {
             settings
                 .characters
@@ -89,16 +93,23 @@ impl AssetLoader for PxTypefaceLoader {
                         .unwrap_or(settings.default_frames)
                         as usize;
 
-                    (
+                    if frames == 0 || image_width < frames {
+                        return Err(anyhow!(
+                            "character `{character}` is too narrow for its {frames} frames"
+                        ));
+                    }
+
+                    Ok((
                         character,
                         PxSpriteAsset {
                             data: PxImage::from_parts_vert(image.split_horz(image_width / frames))
-                                .unwrap(),
+                                .ok_or_else(|| anyhow!("character `{character}` has no frames"))?,
                             frame_size: image_area / frames,
+                            trim: None,
                         },
-                    )
+                    ))
                 })
-                .collect::<HashMap<_, _>>()
+                .collect::<Result<HashMap<_, _>>>()?
         };
 
         let max_frame_count =
@@ -175,6 +186,39 @@ pub struct PxText {
     pub value: String,
     /// The typeface
     pub typeface: Handle<PxTypeface>,
+    /// If set, every character takes up this many pixels horizontally, and is centered
+    /// in that space, so that changing numbers don't shift around. Separators keep their width.
+    pub monospace: Option<u32>,
+    /// If `true` and `monospace` isn't set, every character takes up as much space
+    /// as the typeface's widest digit, as if `monospace` were set to that width
+    pub monospace_digits: bool,
+}
+
+impl PxText {
+    /// Creates monospaced text displaying a number, such as a score or timer.
+    /// Each digit takes up as much space as the typeface's widest digit.
+    pub fn number(number: impl ToString, typeface: Handle<PxTypeface>) -> Self {
+        Self {
+            value: number.to_string(),
+            typeface,
+            monospace: None,
+            monospace_digits: true,
+        }
+    }
+
+    // Horizontal space that each character takes up, if the text is monospaced
+    pub(crate) fn monospace(&self, typeface: &PxTypeface) -> Option<u32> {
+        self.monospace.or_else(|| {
+            self.monospace_digits
+                .then(|| {
+                    ('0'..='9')
+                        .filter_map(|digit| typeface.characters.get(&digit))
+                        .map(|digit| digit.data.width() as u32)
+                        .max()
+                })
+                .flatten()
+        })
+    }
 }
 
 impl AnimatedAssetComponent for PxText {
@@ -200,16 +244,27 @@ pub(crate) type TextComponents<L> = (
 );
 
 fn extract_texts<L: PxLayer>(
-    texts: Extract<Query<(TextComponents<L>, &InheritedVisibility, RenderEntity)>>,
+    texts: Extract<
+        Query<(
+            TextComponents<L>,
+            &InheritedVisibility,
+            Has<PxHidden>,
+            RenderEntity,
+        )>,
+    >,
     mut cmd: Commands,
 ) {
-    for ((text, &rect, &alignment, layer, &canvas, animation, filter), visibility, id) in &texts {
-        if !visibility.get() {
+    for ((text, &rect, &alignment, layer, &canvas, animation, filter), visibility, hidden, id) in
+        &texts
+    {
+        if hidden || !visibility.get() {
+            cmd.entity(id).insert(PxHidden);
             continue;
         }
 
         let mut entity = cmd.entity(id);
         entity.insert((text.clone(), rect, alignment, layer.clone(), canvas));
+        entity.remove::<PxHidden>();
 
         if let Some(animation) = animation {
             entity.insert(*animation);
@@ -1,4 +1,13 @@
-use crate::{position::Spatial, prelude::*};
+use crate::{
+    position::Spatial,
+    prelude::*,
+    set::PxSet,
+    sprite::{PxSpriteAsset, RotatedSprite},
+};
+
+pub(crate) fn plug(app: &mut App) {
+    app.add_systems(PostUpdate, follow_sprites.after(PxSet::PreExtract));
+}
 
 /// UI is displayed within these bounds
 #[derive(Component, Deref, DerefMut, Clone, Copy, Default, Debug)]
@@ -15,3 +24,50 @@ impl Spatial for PxRect {
         self.size().as_uvec2()
     }
 }
+
+/// Keeps this entity's [`PxRect`] covering the given sprite entity's bounds, such as to filter
+/// a highlighted unit. The rect is updated each frame before the frame is extracted. If this entity
+/// and the sprite are on different [`PxCanvas`]es, the rect is converted with [`PxCamera`]
+/// so it still covers the sprite on the screen.
+#[derive(Component, Deref, DerefMut, Clone, Copy, Debug)]
+#[require(PxRect)]
+pub struct PxRectFollow(pub Entity);
+
+fn follow_sprites(
+    mut rects: Query<(&mut PxRect, &PxRectFollow, Option<&PxCanvas>)>,
+    targets: Query<(
+        &PxSprite,
+        &PxPosition,
+        &PxAnchor,
+        Option<&PxAnchorOffset>,
+        Option<&PxRotation>,
+        Option<&PxCanvas>,
+    )>,
+    sprites: Res<Assets<PxSpriteAsset>>,
+    camera: Res<PxCamera>,
+) {
+    for (mut rect, &PxRectFollow(target), canvas) in &mut rects {
+        let Ok((sprite, position, &anchor, anchor_offset, rotation, target_canvas)) =
+            targets.get(target)
+        else {
+            continue;
+        };
+
+        let Some(sprite) = sprites.get(&**sprite) else {
+            continue;
+        };
+
+        let sprite = RotatedSprite(sprite, rotation.copied().unwrap_or_default());
+        let min = **position + anchor_offset.map_or(IVec2::ZERO, |offset| **offset)
+            - sprite.anchor_pos(anchor);
+        let min = camera.screen_to_world(
+            camera.world_to_screen(min, target_canvas.copied().unwrap_or_default()),
+            canvas.copied().unwrap_or_default(),
+        );
+        let new_rect = IRect::from_corners(min, min + sprite.frame_size().as_ivec2());
+
+        if **rect != new_rect {
+            **rect = new_rect;
+        }
+    }
+}